@@ -0,0 +1,111 @@
+//! Derive macros of borealis-rs.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements `borealis_rs::core::bind::BindViews` for a struct, binding
+/// every field annotated with `#[bind("id")]` to the view with that id.
+///
+/// ```ignore
+/// #[derive(BindViews)]
+/// pub struct MainActivity {
+///     #[bind("title")]
+///     title: BoundView<Label>,
+/// }
+/// ```
+#[proc_macro_derive(BindViews, attributes(bind))]
+pub fn derive_bind_views(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match bind_views(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn bind_views(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(input, "BindViews can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(input, "BindViews requires named fields"));
+    };
+
+    let mut binds = vec![];
+    for field in &fields.named {
+        for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident("bind")) {
+            let id: LitStr = attribute.parse_args()?;
+            let name = &field.ident;
+            binds.push(quote! {
+                self.#name.set_id(#id);
+                self.#name.bind(root);
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::borealis_rs::core::bind::BindViews for #name #type_generics #where_clause {
+            fn bind_views(
+                &mut self,
+                root: &::std::rc::Rc<::std::cell::RefCell<::borealis_rs::core::view_base::View>>,
+            ) {
+                #(#binds)*
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bind_views;
+    use syn::{parse_quote, DeriveInput};
+
+    fn expand(input: DeriveInput) -> syn::Result<String> {
+        bind_views(&input).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn binds_the_annotated_fields_only() {
+        let expanded = expand(parse_quote! {
+            struct Screen {
+                #[bind("title")]
+                title: BoundView<Label>,
+                other: BoundView<Label>,
+            }
+        })
+        .unwrap();
+
+        assert!(expanded.contains("BindViews for Screen"));
+        assert!(
+            expanded.contains("self . title . set_id (\"title\") ; self . title . bind (root) ;")
+        );
+        assert!(!expanded.contains("other"));
+    }
+
+    #[test]
+    fn rejects_enums_and_tuple_structs() {
+        let error = expand(parse_quote! { enum Screen { A } }).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "BindViews can only be derived for structs"
+        );
+
+        let error = expand(parse_quote! { struct Screen(BoundView<Label>); }).unwrap_err();
+        assert_eq!(error.to_string(), "BindViews requires named fields");
+    }
+
+    #[test]
+    fn rejects_an_id_that_is_not_a_string() {
+        let input = parse_quote! {
+            struct Screen {
+                #[bind(title)]
+                title: BoundView<Label>,
+            }
+        };
+        assert!(expand(input).is_err());
+    }
+}
@@ -3,9 +3,10 @@ extern crate android_log;
 #[macro_use]
 extern crate log;
 
-use borealis_rs::core::activity::Activity;
+mod demo;
+
 use borealis_rs::core::application;
-use borealis_rs::demo::activity::main_activity::MainActivity;
+use demo::main_activity::MainActivity;
 
 #[ndk_glue::main(backtrace = "on")]
 fn main() {
@@ -13,7 +14,12 @@ fn main() {
 
     let (mut application, event_loop) = application::Application::init("rilirili").unwrap();
 
-    application.push_activity(Activity::MainActivity(MainActivity::new()));
+    demo::add_demo_styles();
+    borealis_rs::core::i18n::load_translations("en-US").unwrap();
+
+    application
+        .push_activity(Box::new(MainActivity::new()))
+        .unwrap();
 
     application.main_loop(event_loop);
 
@@ -0,0 +1,47 @@
+use borealis_rs::core::activity::{ActivityDyn, ActivityViewData};
+use borealis_rs::core::bind::{BindViews, BoundView};
+use borealis_rs::core::view_base::View;
+use borealis_rs::core::view_creator::ViewCreator;
+use borealis_rs::views::label::{Label, LabelTrait};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(BindViews)]
+pub struct MainActivity {
+    activity_view_data: ActivityViewData,
+
+    #[bind("title")]
+    title: BoundView<Label>,
+}
+
+impl MainActivity {
+    pub fn new() -> Self {
+        Self {
+            activity_view_data: ActivityViewData::new(),
+            title: BoundView::default(),
+        }
+    }
+}
+
+impl ViewCreator for MainActivity {}
+
+impl ActivityDyn for MainActivity {
+    fn view_data(&self) -> &ActivityViewData {
+        &self.activity_view_data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ActivityViewData {
+        &mut self.activity_view_data
+    }
+
+    fn bind_content_view(&mut self, content_view: &Rc<RefCell<View>>) {
+        self.bind_views(content_view);
+    }
+
+    fn on_content_available(&self) {
+        match self.title.get() {
+            Ok(title) => info!("main activity title: {}", title.full_text()),
+            Err(e) => error!("{}", e),
+        }
+    }
+}
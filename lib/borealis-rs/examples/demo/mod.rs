@@ -0,0 +1,13 @@
+//! The activity and the styles of the demo application, shared by the examples.
+
+pub mod main_activity;
+
+use borealis_rs::core::style::add_style;
+
+/// Registers the metrics used by the demo XML layouts.
+pub fn add_demo_styles() {
+    add_style("about/padding_top_bottom", 50.0);
+    add_style("about/padding_sides", 75.0);
+    add_style("about/description_margin", 50.0);
+}
+
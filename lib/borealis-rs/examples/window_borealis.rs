@@ -2,10 +2,13 @@ extern crate env_logger;
 #[macro_use]
 extern crate log;
 
-use borealis_rs::core::activity::Activity;
+mod demo;
+
 use borealis_rs::core::application;
-use borealis_rs::demo::activity::main_activity::MainActivity;
+use borealis_rs::core::recording::InputRecording;
+use demo::main_activity::MainActivity;
 use log::LevelFilter::Trace;
+use std::path::PathBuf;
 
 fn main() -> anyhow::Result<()> {
     // 初始化 env_logger
@@ -14,11 +17,41 @@ fn main() -> anyhow::Result<()> {
         .target(env_logger::Target::Stdout) // 将日志输出到 stdout
         .init();
 
-    let (mut application, event_loop) = application::Application::init("rilirili")?;
+    // --record-input FILE: saves the input to FILE when the window is closed
+    // --replay-input FILE: replays the input of FILE, add --headless to do it without a window
+    let mut record = None;
+    let mut replay = None;
+    let mut headless = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record-input" => record = args.next().map(PathBuf::from),
+            "--replay-input" => replay = args.next().map(PathBuf::from),
+            "--headless" => headless = true,
+            _ => anyhow::bail!("unknown option {}", arg),
+        }
+    }
+
+    let mut application = application::Application::new("rilirili")?;
+
+    demo::add_demo_styles();
+    borealis_rs::core::i18n::load_translations("en-US")?;
+
+    application.push_activity(Box::new(MainActivity::new()))?;
 
-    application.push_activity(Activity::MainActivity(MainActivity::new()));
+    if let Some(path) = record {
+        application.record_input_to(path);
+    }
+    if let Some(path) = replay {
+        application.start_replay(InputRecording::load(&path)?);
+        if headless {
+            application.run_replay();
+            info!("replay done");
+            return Ok(());
+        }
+    }
 
-    application.main_loop(event_loop);
+    application.main_loop(winit::event_loop::EventLoop::new());
 
     info!("main_loop done");
 
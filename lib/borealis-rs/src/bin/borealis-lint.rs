@@ -0,0 +1,127 @@
+//! Checks the XML layouts of a resources directory without opening a window.
+//!
+//! Reports unknown tags and attributes, invalid values, unresolved
+//! @style/@theme/@i18n/@res references, include cycles and duplicate ids,
+//! then exits with a non-zero status if any problem was found.
+
+use borealis_rs::core::i18n::load_translations;
+use borealis_rs::core::lint::lint_directory;
+use borealis_rs::core::resource::{set_resources_path, CUSTOM_RESOURCES_PATH};
+use borealis_rs::core::style::add_style;
+use borealis_rs::core::view_base::View;
+use borealis_rs::core::view_box::{Axis, BoxEnum, BoxView};
+use borealis_rs::core::view_creator::XMLViewRegistry;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: borealis-lint [OPTIONS] [RESOURCES]
+
+Checks every XML file of the RESOURCES directory (default: \"resources\").
+
+Options:
+  --locale <LOCALE>     translations to resolve @i18n/ references with (default: en-US)
+  --view <TAG>          declares a custom view registered by the application,
+                        checked as a brls:Box
+  --style <KEY=VALUE>   declares a style metric added by the application
+  -h, --help            prints this message";
+
+struct Options {
+    resources: PathBuf,
+    locale: String,
+    views: Vec<String>,
+    styles: Vec<(String, f32)>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        resources: PathBuf::from(CUSTOM_RESOURCES_PATH),
+        locale: "en-US".into(),
+        views: vec![],
+        styles: vec![],
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Err(String::new()),
+            "--locale" => options.locale = value("--locale")?,
+            "--view" => options.views.push(value("--view")?),
+            "--style" => {
+                let style = value("--style")?;
+                let (key, metric) = style
+                    .split_once('=')
+                    .and_then(|(key, metric)| Some((key, metric.trim().parse().ok()?)))
+                    .ok_or(format!("invalid style \"{}\", expected KEY=VALUE", style))?;
+                options.styles.push((key.into(), metric));
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => options.resources = PathBuf::from(arg),
+        }
+    }
+
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("error: {}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    set_resources_path(options.resources.clone());
+
+    if let Err(e) = load_translations(&options.locale) {
+        eprintln!("warning: {:#}, @i18n/ references will not resolve", e);
+    }
+
+    for (key, metric) in &options.styles {
+        add_style(key, *metric);
+    }
+
+    let mut registry = XMLViewRegistry::new();
+    for view in &options.views {
+        let result = registry.register(
+            view,
+            Box::new(|| View::Box(BoxEnum::Box(BoxView::with_axis(Axis::Row)))),
+        );
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    }
+
+    let report = match lint_directory(&registry, &options.resources) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    for diagnostic in &report.diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    match report.diagnostics.len() {
+        0 => {
+            println!("{} XML file(s) checked, no problem found", report.files.len());
+            ExitCode::SUCCESS
+        }
+        count => {
+            println!(
+                "{} XML file(s) checked, {} problem(s) found",
+                report.files.len(),
+                count
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
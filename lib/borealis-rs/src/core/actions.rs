@@ -0,0 +1,168 @@
+use crate::core::input::ControllerButton;
+use crate::core::view_base::{View, ViewBase};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub type ActionIdentifier = i32;
+
+/// Called with the view owning the action when its button is pressed.
+/// Returns true if the press was consumed, false to let the parents handle it.
+pub type ActionListener = Rc<dyn Fn(Rc<RefCell<View>>) -> bool>;
+
+/// An action registered on a view, triggered by a controller button
+/// while the focus is on that view or one of its children.
+#[derive(Clone)]
+pub struct Action {
+    pub button: ControllerButton,
+    pub identifier: ActionIdentifier,
+    pub hint_text: String,
+    pub available: bool,
+    pub hidden: bool,
+    pub listener: ActionListener,
+}
+
+/**
+ * Returns the actions to display as hints when the focus is on the given view:
+ * the actions of the view and its parents that are not hidden, the closest
+ * view winning when several of them use the same button.
+ * Unavailable actions are kept, to be displayed as disabled.
+ *
+ * Start comes first, then the other buttons in the order they were found,
+ * then B and A.
+ */
+pub fn hint_actions(view: Option<Rc<RefCell<View>>>) -> Vec<Action> {
+    let mut actions: Vec<Action> = vec![];
+    let mut buttons = vec![];
+
+    let mut current = view;
+    while let Some(view) = current {
+        let view = match view.try_borrow() {
+            Ok(view) => view,
+            Err(_) => break,
+        };
+
+        for action in &view.data().actions {
+            if action.hidden || buttons.contains(&action.button) {
+                continue;
+            }
+
+            buttons.push(action.button);
+            actions.push(action.clone());
+        }
+
+        current = view.parent();
+    }
+
+    actions.sort_by_key(|action| match action.button {
+        ControllerButton::Start => 0,
+        ControllerButton::B => 2,
+        ControllerButton::A => 3,
+        _ => 1,
+    });
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hint_actions;
+    use crate::core::input::ControllerButton;
+    use crate::core::view_base::{View, ViewBase};
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use crate::core::view_layout::ViewLayout;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Returns a box and the label it contains
+    fn box_with_label() -> (Rc<RefCell<View>>, Rc<RefCell<View>>) {
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box>
+            <brls:Label id="label"/>
+        </brls:Box>"#;
+        let root = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        let label = root.borrow().get_view("label").unwrap();
+        (root, label)
+    }
+
+    fn register(view: &Rc<RefCell<View>>, hint_text: &str, button: ControllerButton, hidden: bool) {
+        view.borrow_mut()
+            .register_action(hint_text, button, Box::new(|_| true), hidden);
+    }
+
+    fn hints(view: &Rc<RefCell<View>>) -> Vec<(ControllerButton, String)> {
+        hint_actions(Some(view.clone()))
+            .into_iter()
+            .map(|action| (action.button, action.hint_text))
+            .collect()
+    }
+
+    #[test]
+    fn start_comes_first_then_b_and_a() {
+        let (_root, label) = box_with_label();
+        register(&label, "Ok", ControllerButton::A, false);
+        register(&label, "Back", ControllerButton::B, false);
+        register(&label, "Search", ControllerButton::Y, false);
+        register(&label, "Menu", ControllerButton::Start, false);
+        register(&label, "Sort", ControllerButton::X, false);
+
+        assert_eq!(
+            hints(&label),
+            vec![
+                (ControllerButton::Start, "Menu".into()),
+                (ControllerButton::Y, "Search".into()),
+                (ControllerButton::X, "Sort".into()),
+                (ControllerButton::B, "Back".into()),
+                (ControllerButton::A, "Ok".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn closest_view_wins() {
+        let (root, label) = box_with_label();
+        register(&root, "Outer", ControllerButton::A, false);
+        register(&root, "Search", ControllerButton::Y, false);
+        register(&label, "Inner", ControllerButton::A, false);
+
+        assert_eq!(
+            hints(&label),
+            vec![
+                (ControllerButton::Y, "Search".into()),
+                (ControllerButton::A, "Inner".into()),
+            ]
+        );
+        assert_eq!(hints(&root)[1], (ControllerButton::A, "Outer".into()));
+    }
+
+    #[test]
+    fn hidden_actions_are_skipped_and_unavailable_ones_kept() {
+        let (_root, label) = box_with_label();
+        register(&label, "Secret", ControllerButton::Y, true);
+        register(&label, "Ok", ControllerButton::A, false);
+        label
+            .borrow_mut()
+            .set_action_available(ControllerButton::A, false);
+
+        let actions = hint_actions(Some(label));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].button, ControllerButton::A);
+        assert!(!actions[0].available);
+    }
+
+    #[test]
+    fn identifiers_are_never_reused() {
+        let (_root, label) = box_with_label();
+        let mut label = label.borrow_mut();
+        let ok = label.register_action("Ok", ControllerButton::A, Box::new(|_| true), false);
+        let back = label.register_action("Back", ControllerButton::B, Box::new(|_| true), false);
+        label.unregister_action(back);
+        let sort = label.register_action("Sort", ControllerButton::X, Box::new(|_| true), false);
+        assert!(ok != back && sort != back && sort != ok);
+
+        // The old identifier does not unregister the new action
+        label.unregister_action(back);
+        assert!(label.action(ControllerButton::X).is_some());
+        assert!(label.action(ControllerButton::A).is_some());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::core::global::{content_height, content_width};
-use crate::core::view_base::View;
-use crate::core::view_creator::ViewCreator;
+use crate::core::resource::resources_path;
+use crate::core::view_base::{View, ViewBase};
+use crate::core::view_creator::{ViewCreator, XMLViewRegistry};
+use crate::core::view_drawer::ViewDrawer;
 use crate::core::view_layout::ViewLayout;
 use std::cell::RefCell;
 use std::path::PathBuf;
@@ -9,6 +11,9 @@ use std::rc::Rc;
 pub struct ActivityViewData {
     pub xml_path: PathBuf,
     pub content_view: Option<Rc<RefCell<View>>>,
+    /// XML files the content view was inflated from, the activity file
+    /// and the ones it includes, watched by the hot-reload feature
+    pub xml_files: Vec<PathBuf>,
 }
 
 impl ActivityViewData {
@@ -16,6 +21,7 @@ impl ActivityViewData {
         Self {
             xml_path: "activity/box.xml".parse().unwrap(),
             content_view: None,
+            xml_files: vec![],
         }
     }
 }
@@ -24,14 +30,39 @@ pub trait ActivityDyn: ViewCreator {
     fn view_data(&self) -> &ActivityViewData;
     fn view_data_mut(&mut self) -> &mut ActivityViewData;
 
-    fn create_content_view(&self) -> Rc<RefCell<View>> {
-        self.create_from_xml_resource(self.view_data().xml_path.clone())
+    fn create_content_view(&self, registry: &XMLViewRegistry) -> anyhow::Result<Rc<RefCell<View>>> {
+        self.create_content_view_with_files(registry).0
+    }
+
+    /**
+     * Inflates the content view like create_content_view(), also returning
+     * every XML file inflated for it, see ViewCreator::create_from_xml_file_with_files().
+     */
+    fn create_content_view_with_files(
+        &self,
+        registry: &XMLViewRegistry,
+    ) -> (anyhow::Result<Rc<RefCell<View>>>, Vec<PathBuf>) {
+        self.create_from_xml_file_with_files(registry, self.content_view_file())
+    }
+
+    /**
+     * Returns the path of the XML file the content view is inflated from.
+     */
+    fn content_view_file(&self) -> PathBuf {
+        resources_path().join("xml").join(&self.view_data().xml_path)
     }
 
     fn set_content_view(&mut self, view: Rc<RefCell<View>>) {
+        self.bind_content_view(&view);
         self.view_data_mut().content_view = Some(view);
     }
 
+    /**
+     * Called when the content view is set, before on_content_available().
+     * Activities deriving BindViews bind their BoundView fields here.
+     */
+    fn bind_content_view(&mut self, _content_view: &Rc<RefCell<View>>) {}
+
     fn resize_to_fit_window(&self) {
         match &self.view_data().content_view {
             None => {}
@@ -42,27 +73,85 @@ pub trait ActivityDyn: ViewCreator {
         }
     }
 
+    /**
+     * Returns the view with the corresponding id in the content view,
+     * or None if it hasn't been found.
+     */
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        self.view_data()
+            .content_view
+            .as_ref()
+            .and_then(|view| view.borrow().get_view(id))
+    }
+
+    /**
+     * Returns the view to focus when the activity is shown.
+     */
+    fn default_focus(&self) -> Option<Rc<RefCell<View>>> {
+        self.view_data()
+            .content_view
+            .as_ref()
+            .and_then(|view| view.borrow().default_focus())
+    }
+
     fn on_content_available(&self) {}
 
+    /**
+     * Returns true if the activities below this one can be seen through it,
+     * like a dialog. Only the activities that can be seen are drawn.
+     */
+    fn is_translucent(&self) -> bool {
+        false
+    }
+
     fn on_window_size_changed(&self) {
         self.resize_to_fit_window();
     }
-}
-
-pub enum Activity {
-    MainActivity(crate::demo::activity::main_activity::MainActivity),
-}
 
-impl ActivityDyn for Activity {
-    fn view_data(&self) -> &ActivityViewData {
-        match self {
-            Activity::MainActivity(a) => a.view_data(),
+    /**
+     * Called when the activity is about to be shown, when pushed
+     * or when the activity above it is popped. Forwarded to
+     * the content view by default.
+     *
+     * reset_state is true when the activity is pushed.
+     */
+    fn will_appear(&self, reset_state: bool) {
+        if let Some(view) = &self.view_data().content_view {
+            view.borrow().will_appear(reset_state);
         }
     }
 
-    fn view_data_mut(&mut self) -> &mut ActivityViewData {
-        match self {
-            Activity::MainActivity(a) => a.view_data_mut(),
+    /**
+     * Called when the activity is about to be hidden, when popped
+     * or when another activity is pushed above it. Forwarded to
+     * the content view by default.
+     *
+     * reset_state is true when the activity is popped.
+     */
+    fn will_disappear(&self, reset_state: bool) {
+        if let Some(view) = &self.view_data().content_view {
+            view.borrow().will_disappear(reset_state);
         }
     }
+
+    /**
+     * Called when the activity becomes the top one and receives the input.
+     */
+    fn on_resume(&self) {}
+
+    /**
+     * Called when the activity stops being the top one.
+     */
+    fn on_pause(&self) {}
+
+    /**
+     * Called once the activity has been popped, as the last callback it receives.
+     */
+    fn on_destroy(&self) {}
 }
+
+/// An activity of the stack of the application. Applications define
+/// their own by implementing ActivityDyn (and ViewCreator), then push them boxed:
+///
+/// application.push_activity(Box::new(MainActivity::new()))?;
+pub type Activity = Box<dyn ActivityDyn>;
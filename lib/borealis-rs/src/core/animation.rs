@@ -1,91 +1,605 @@
-use crate::core::time::{FiniteTicking, Ticking};
+use crate::core::time::{FiniteTicking, Ticking, TickingState, Time};
 use crate::core::tweening::EasingFunction;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Mutex;
 
 /// An animatable is a float which value can be animated from an initial value to a target value,
 /// during a given amount of time. An easing function can also be specified.
 ///
-/// Declare the animatable and then use reset(initialValue) to reset the animation.
-/// Add as many steps as you like by calling addStep(targetValue, duration, easing) one or multiple times.
+/// Declare the animatable and then use reset_initial(initial_value) to reset the animation.
+/// Add as many steps as you like by calling add_step_easing(target_value, duration, easing) one or multiple times.
 /// Then, start and stop the animation with start() and stop().
 ///
-/// setEndCallback() and setTickCallback() allow you to execute code as long as the animation runs and / or once when it finishes.
-/// Use .getValue() to get the current value at any time.
-///
-/// An animatable has overloads for float conversion, comparison (==) and assignment operator (=) to allow
-/// basic usage as a simple float. Assignment operator is a shortcut to the reset() method.
-
+/// set_end_callback() and set_tick_callback() allow you to execute code as long as the animation runs and / or once when it finishes.
+/// Use value() to get the current value at any time.
 pub struct Animatable {
-    pub current_value: f32,
+    state: Rc<AnimatableState>,
+}
+
+struct AnimatableState {
+    ticking: TickingState,
+    initial_value: Cell<f32>,
+    current_value: Cell<f32>,
+    steps: RefCell<Vec<AnimationStep>>,
+    elapsed: Cell<Time>,
+}
+
+/// One step of an animation, going from the target of
+/// the previous step (or the initial value) to its own target.
+#[derive(Debug, Copy, Clone)]
+struct AnimationStep {
+    target_value: f32,
+    duration: Time,
+    easing: EasingFunction,
 }
 
 impl Animatable {
     pub fn new(value: f32) -> Self {
         Self {
-            current_value: value
+            state: Rc::new(AnimatableState {
+                ticking: TickingState::new(),
+                initial_value: Cell::new(value),
+                current_value: Cell::new(value),
+                steps: RefCell::new(vec![]),
+                elapsed: Cell::new(0),
+            }),
         }
     }
+
+    /**
+     * Adds a linear animation step, see add_step_easing().
+     */
+    pub fn add_step(&self, target_value: f32, duration: i32) {
+        self.add_step_easing(target_value, duration, EasingFunction::Linear);
+    }
+
+    /**
+     * Returns the total duration of all the steps in ms.
+     */
+    pub fn duration(&self) -> Time {
+        self.state.steps.borrow().iter().map(|step| step.duration).sum()
+    }
+
+    /**
+     * Returns true if the animation went through all of its steps.
+     */
+    pub fn is_finished(&self) -> bool {
+        self.state.elapsed.get() >= self.duration()
+    }
+
+    fn value_at(&self, elapsed: Time) -> f32 {
+        let mut from = self.state.initial_value.get();
+        let mut time = elapsed;
+
+        for step in self.state.steps.borrow().iter() {
+            if time < step.duration {
+                let t = time as f32 / step.duration as f32;
+                return from + (step.target_value - from) * step.easing.apply(t);
+            }
+
+            time -= step.duration;
+            from = step.target_value;
+        }
+
+        from
+    }
 }
 
-impl FiniteTicking for Animatable {}
+impl Ticking for Animatable {
+    fn ticking_state(&self) -> &TickingState {
+        &self.state.ticking
+    }
 
-impl Ticking for Animatable {}
+    fn ticking_handle(&self) -> Box<dyn Ticking> {
+        Box::new(Animatable {
+            state: self.state.clone(),
+        })
+    }
 
-impl Animating for Animatable {
+    fn on_update(&self, delta: Time) -> bool {
+        let elapsed = (self.state.elapsed.get() + delta).min(self.duration());
+        self.state.elapsed.set(elapsed);
+        self.state.current_value.set(self.value_at(elapsed));
 
+        !self.is_finished()
+    }
+
+    fn on_start(&self) {
+        // Starting a finished animation plays it again
+        if self.is_finished() {
+            self.rewind();
+        }
+    }
 }
 
-pub trait Animating: FiniteTicking {
+impl FiniteTicking for Animatable {
+    fn on_rewind(&self) {
+        self.state.elapsed.set(0);
+        self.state.current_value.set(self.state.initial_value.get());
+    }
 
-    /**
-     * Returns the current animatable value.
-     */
+    fn on_reset(&self) {
+        self.state.steps.borrow_mut().clear();
+        self.state.elapsed.set(0);
+        self.state.initial_value.set(self.state.current_value.get());
+    }
+}
+
+impl Animating for Animatable {
     fn value(&self) -> f32 {
-        todo!()
+        self.state.current_value.get()
     }
 
+    fn reset_initial(&self, initial_value: f32) {
+        self.reset();
+        self.state.initial_value.set(initial_value);
+        self.state.current_value.set(initial_value);
+    }
+
+    fn add_step_easing(&self, target_value: f32, duration: i32, easing: EasingFunction) {
+        self.state.steps.borrow_mut().push(AnimationStep {
+            target_value,
+            duration: duration.max(0) as Time,
+            easing,
+        });
+    }
+
+    fn progress(&self) -> f32 {
+        let duration = self.duration();
+        if duration == 0 {
+            return 1.0;
+        }
+
+        self.state.elapsed.get() as f32 / duration as f32
+    }
+}
+
+/// A value animated through a list of steps, see Animatable.
+///
+/// Use reset() from FiniteTicking to stop and reset the animation while
+/// keeping the current value.
+pub trait Animating: FiniteTicking {
     /**
-     * Stops and resets the animation, going back to the given initial value.
-     * All steps are removed.
-     * If an animation was already ongoing for that animatable, its end callback
-     * will be called.
+     * Returns the current animatable value.
      */
-    fn reset_initial(&mut self, initial_value: f32) {
-        todo!()
-    }
+    fn value(&self) -> f32;
 
     /**
-     * Stops and resets the animation. The value will stay where it's at.
+     * Stops and resets the animation, going back to the given initial value.
      * All steps are removed.
      * If an animation was already ongoing for that animatable, its end callback
      * will be called.
      */
-    fn reset(&mut self) {}
+    fn reset_initial(&self, initial_value: f32);
 
     /**
      * Adds an animation step to the target value, lasting the specified duration in milliseconds.
      *
      * An animation can have multiple steps. Target value can be greater and lower than the previous step (it can go forwards or backwards).
-     * Easing function is optional, default is EasingFunction::linear.
+     * Use EasingFunction::Linear for a linear step.
      *
-     * Duration is int32_t due to internal limitations, so a step cannot last for longer than 2 147 483 647ms.
-     * The sum of the duration of all steps cannot exceed 71582min.
+     * Duration is i32 due to internal limitations, so a step cannot last for longer than 2 147 483 647ms.
+     * Negative durations are treated as 0.
+     */
+    fn add_step_easing(&self, target_value: f32, duration: i32, easing: EasingFunction);
+
+    /**
+     * Returns the progress of the animation between 0.0 and 1.0.
+     */
+    fn progress(&self) -> f32;
+}
+
+/// Default stiffness of a Spring, in N/m.
+pub const SPRING_DEFAULT_STIFFNESS: f32 = 170.0;
+/// Default damping of a Spring, in N·s/m.
+pub const SPRING_DEFAULT_DAMPING: f32 = 26.0;
+/// Default mass of a Spring, in kg.
+pub const SPRING_DEFAULT_MASS: f32 = 1.0;
+
+/// Longest simulation step, longer frames are split to keep the integration stable
+const SPRING_MAX_STEP: f32 = 1.0 / 240.0;
+
+/// A float pulled towards a target value by a damped spring, as an alternative
+/// to the fixed duration steps of Animatable.
+///
+/// Changing the target while the spring moves keeps its velocity, so interrupted
+/// animations (scrolling, focus movement...) retarget smoothly.
+///
+/// The spring stops on its own once settled, that is once both the distance to the target
+/// and the velocity are under the rest thresholds; its end callback then gets true.
+pub struct Spring {
+    state: Rc<SpringState>,
+}
+
+struct SpringState {
+    ticking: TickingState,
+    value: Cell<f32>,
+    velocity: Cell<f32>,
+    target: Cell<f32>,
+    stiffness: Cell<f32>,
+    damping: Cell<f32>,
+    mass: Cell<f32>,
+    rest_delta: Cell<f32>,
+    rest_velocity: Cell<f32>,
+}
+
+impl Spring {
+    pub fn new(value: f32) -> Self {
+        Self {
+            state: Rc::new(SpringState {
+                ticking: TickingState::new(),
+                value: Cell::new(value),
+                velocity: Cell::new(0.0),
+                target: Cell::new(value),
+                stiffness: Cell::new(SPRING_DEFAULT_STIFFNESS),
+                damping: Cell::new(SPRING_DEFAULT_DAMPING),
+                mass: Cell::new(SPRING_DEFAULT_MASS),
+                rest_delta: Cell::new(0.001),
+                rest_velocity: Cell::new(0.001),
+            }),
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.state.value.get()
+    }
+
+    /**
+     * Returns the current velocity, in units per second.
+     */
+    pub fn velocity(&self) -> f32 {
+        self.state.velocity.get()
+    }
+
+    pub fn target(&self) -> f32 {
+        self.state.target.get()
+    }
+
+    /**
+     * Sets the value the spring moves to and starts it if needed.
+     * The current velocity is kept.
+     */
+    pub fn set_target(&self, target: f32) {
+        self.state.target.set(target);
+        if !self.is_settled() {
+            self.start();
+        }
+    }
+
+    /**
+     * Sets the current velocity, in units per second,
+     * for instance to carry over the velocity of a pan gesture.
+     */
+    pub fn set_velocity(&self, velocity: f32) {
+        self.state.velocity.set(velocity);
+        if !self.is_settled() {
+            self.start();
+        }
+    }
+
+    /**
+     * Stops the spring and moves it to the given value without animation.
      */
-    fn add_step_easing(target_value: f32, duration: i32, easing: EasingFunction) {
+    pub fn jump_to(&self, value: f32) {
+        self.stop();
+        self.state.value.set(value);
+        self.state.target.set(value);
+        self.state.velocity.set(0.0);
+    }
+
+    pub fn set_stiffness(&self, stiffness: f32) {
+        self.state.stiffness.set(stiffness);
+    }
 
+    pub fn set_damping(&self, damping: f32) {
+        self.state.damping.set(damping);
+    }
+
+    /**
+     * Sets the mass of the spring. Must be greater than 0.
+     */
+    pub fn set_mass(&self, mass: f32) {
+        self.state.mass.set(mass.max(f32::EPSILON));
+    }
+
+    /**
+     * Sets the distance to the target and the velocity under which
+     * the spring is considered settled.
+     */
+    pub fn set_rest_thresholds(&self, rest_delta: f32, rest_velocity: f32) {
+        self.state.rest_delta.set(rest_delta);
+        self.state.rest_velocity.set(rest_velocity);
     }
 
     /**
-     * Returns the progress of the animation between 0.0f and 1.0f.
+     * Returns true if the spring is at rest on its target.
      */
-    fn progress() -> f32 {
-        todo!()
+    pub fn is_settled(&self) -> bool {
+        (self.value() - self.target()).abs() < self.state.rest_delta.get()
+            && self.velocity().abs() < self.state.rest_velocity.get()
+    }
+
+    fn integrate(&self, step: f32) {
+        let state = &self.state;
+        let displacement = state.value.get() - state.target.get();
+        let force = -state.stiffness.get() * displacement - state.damping.get() * state.velocity.get();
+
+        // Semi-implicit Euler
+        let velocity = state.velocity.get() + force / state.mass.get() * step;
+        state.velocity.set(velocity);
+        state.value.set(state.value.get() + velocity * step);
     }
 }
 
-pub fn update_highlight_animation() {
+impl Ticking for Spring {
+    fn ticking_state(&self) -> &TickingState {
+        &self.state.ticking
+    }
+
+    fn ticking_handle(&self) -> Box<dyn Ticking> {
+        Box::new(Spring {
+            state: self.state.clone(),
+        })
+    }
+
+    fn on_update(&self, delta: Time) -> bool {
+        let mut remaining = delta as f32 / 1000.0;
+        while remaining > 0.0 {
+            let step = remaining.min(SPRING_MAX_STEP);
+            self.integrate(step);
+            remaining -= step;
+        }
+
+        if self.is_settled() {
+            self.state.value.set(self.target());
+            self.state.velocity.set(0.0);
+            return false;
+        }
+
+        true
+    }
+}
+
+lazy_static! {
+    static ref HIGHLIGHT_ANIMATION: Mutex<(f32, f32, f32)> = Mutex::new((0.0, 0.0, 0.0));
+}
+
+/**
+ * Updates the highlight gradient position and pulsation.
+ * Called once per frame by the application with the current time in microseconds.
+ */
+pub fn update_highlight_animation(now: Time) {
+    // In f64, milliseconds since the epoch don't fit in a f32
+    let time = (now / 1000) as f64;
+
+    let gradient_x = ((time / 500.0 / 3.0).cos() + 1.0) / 2.0;
+    let gradient_y = ((time / 500.0 / 3.0).sin() + 1.0) / 2.0;
+    let color = ((time / 500.0 * 2.0).sin() + 1.0) / 2.0;
+
+    *HIGHLIGHT_ANIMATION.lock().unwrap() = (gradient_x as f32, gradient_y as f32, color as f32);
+}
+
+/**
+ * Returns the position of the highlight gradient, between 0.0 and 1.0 on each axis,
+ * and the pulsation factor between the two highlight colors.
+ */
+pub fn highlight_animation(gradient_x: &mut f32, gradient_y: &mut f32, color: &mut f32) {
+    (*gradient_x, *gradient_y, *color) = *HIGHLIGHT_ANIMATION.lock().unwrap();
+}
 
+/**
+ * Offset of the highlight shaking after t (in tens of ms)
+ * with the given amplitude: a damped sine wave.
+ */
+pub fn shake_animation(t: f32, amplitude: f32) -> f32 {
+    let w = 0.8;
+    let c = 0.35;
+    amplitude * (-(c * t)).exp() * (w * t).sin()
 }
 
-pub fn highlight_animation(gradient_x: &mut f32, gradient_y: &mut f32, gradient_z: &mut f32) {
+#[cfg(test)]
+mod tests {
+    use super::{Animatable, Animating, Spring};
+    use crate::core::time::{Clock, FiniteTicking, ManualClock, Ticking, TickingScheduler};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
 
-}
\ No newline at end of file
+    /**
+     * Runs 60 frames per second until the scheduler stops ticking,
+     * returning the number of frames.
+     */
+    fn run_until_idle(clock: &ManualClock, scheduler: &TickingScheduler) -> u32 {
+        let mut frames = 0;
+        while scheduler.is_ticking() {
+            clock.advance(16);
+            scheduler.update_tickings(clock.now_usec());
+            frames += 1;
+            assert!(frames < 1000, "the spring never settled");
+        }
+        frames
+    }
+
+    #[test]
+    fn spring_settles_on_its_target() {
+        let clock = ManualClock::new(1_000_000);
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+
+        let finished = Rc::new(Cell::new(None));
+        let spring = Spring::new(0.0);
+        let end = finished.clone();
+        spring.set_end_callback(Box::new(move |finished| end.set(Some(finished))));
+        spring.set_target(100.0);
+        assert!(spring.is_running());
+        assert!(!spring.is_settled());
+
+        scheduler.update_tickings(clock.now_usec());
+        run_until_idle(&clock, &scheduler);
+
+        assert!(spring.is_settled());
+        assert!(!spring.is_running());
+        assert_eq!(spring.value(), 100.0);
+        assert_eq!(spring.velocity(), 0.0);
+        assert_eq!(finished.get(), Some(true));
+    }
+
+    #[test]
+    fn spring_keeps_its_velocity_when_retargeted() {
+        let clock = ManualClock::new(1_000_000);
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+
+        let spring = Spring::new(0.0);
+        spring.set_target(100.0);
+        scheduler.update_tickings(clock.now_usec());
+        for _ in 0..10 {
+            clock.advance(16);
+            scheduler.update_tickings(clock.now_usec());
+        }
+
+        let velocity = spring.velocity();
+        assert!(velocity > 0.0);
+        assert!(spring.value() > 0.0 && spring.value() < 100.0);
+
+        spring.set_target(-50.0);
+        assert_eq!(spring.velocity(), velocity);
+        assert!(spring.is_running());
+
+        run_until_idle(&clock, &scheduler);
+        assert_eq!(spring.value(), -50.0);
+    }
+
+    #[test]
+    fn settled_spring_does_not_tick() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+
+        let spring = Spring::new(10.0);
+        spring.set_target(10.0);
+        assert!(spring.is_settled());
+        assert!(!spring.is_running());
+        assert!(!scheduler.is_ticking());
+    }
+
+    /// Goes from 0 to 10 in 100 ms, then to -10 in 100 ms.
+    fn two_steps() -> Animatable {
+        let animatable = Animatable::new(0.0);
+        animatable.add_step(10.0, 100);
+        animatable.add_step(-10.0, 100);
+        animatable
+    }
+
+    #[test]
+    fn value_goes_through_every_step() {
+        let animatable = two_steps();
+        assert_eq!(animatable.value_at(0), 0.0);
+        assert_eq!(animatable.value_at(50), 5.0);
+        assert_eq!(animatable.value_at(100), 10.0);
+        assert_eq!(animatable.value_at(150), 0.0);
+        assert_eq!(animatable.value_at(200), -10.0);
+        assert_eq!(animatable.value_at(1000), -10.0);
+    }
+
+    #[test]
+    fn zero_duration_step_jumps_to_its_target() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let animatable = Animatable::new(0.0);
+        animatable.add_step(5.0, 0);
+        animatable.add_step(10.0, 100);
+        assert_eq!(animatable.value_at(0), 5.0);
+        assert_eq!(animatable.value_at(50), 7.5);
+
+        let instant = Animatable::new(0.0);
+        instant.add_step(1.0, 0);
+        instant.start();
+        scheduler.update(0);
+        assert_eq!(instant.value(), 1.0);
+        assert!(!instant.is_running());
+    }
+
+    #[test]
+    fn progress_covers_every_step() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        assert_eq!(Animatable::new(0.0).progress(), 1.0);
+
+        let animatable = two_steps();
+        animatable.start();
+        assert_eq!(animatable.progress(), 0.0);
+        scheduler.update(50);
+        assert_eq!(animatable.progress(), 0.25);
+        scheduler.update(1000);
+        assert_eq!(animatable.progress(), 1.0);
+    }
+
+    #[test]
+    fn reset_keeps_the_current_value() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let ends = Rc::new(RefCell::new(vec![]));
+        let callback_ends = ends.clone();
+        let animatable = two_steps();
+        animatable.set_end_callback(Box::new(move |finished| {
+            callback_ends.borrow_mut().push(finished)
+        }));
+        animatable.start();
+        scheduler.update(50);
+
+        animatable.reset();
+        assert!(!animatable.is_running());
+        assert_eq!(animatable.value(), 5.0);
+        assert_eq!(animatable.duration(), 0);
+        assert_eq!(*ends.borrow(), vec![false]);
+
+        // Steps added after a reset start from the kept value
+        animatable.add_step(15.0, 100);
+        assert_eq!(animatable.value_at(50), 10.0);
+
+        animatable.reset_initial(2.0);
+        assert_eq!(animatable.value(), 2.0);
+        assert_eq!(animatable.duration(), 0);
+    }
+
+    #[test]
+    fn starting_a_finished_animation_rewinds_it() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let animatable = two_steps();
+        animatable.start();
+        scheduler.update(200);
+        assert!(animatable.is_finished());
+        assert_eq!(animatable.value(), -10.0);
+
+        animatable.start();
+        assert!(animatable.is_running());
+        assert_eq!(animatable.value(), 0.0);
+        scheduler.update(50);
+        assert_eq!(animatable.value(), 5.0);
+    }
+
+    #[test]
+    fn last_tick_comes_before_the_end_callback() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let animatable = Rc::new(two_steps());
+        let events = Rc::new(RefCell::new(vec![]));
+
+        let tick_events = events.clone();
+        let tick_animatable = Rc::downgrade(&animatable);
+        animatable.set_tick_callback(Box::new(move || {
+            let value = tick_animatable.upgrade().unwrap().value();
+            tick_events.borrow_mut().push(format!("tick {}", value));
+        }));
+        let end_events = events.clone();
+        animatable.set_end_callback(Box::new(move |finished| {
+            end_events.borrow_mut().push(format!("end {}", finished));
+        }));
+        animatable.start();
+
+        scheduler.update(150);
+        scheduler.update(100);
+        assert_eq!(*events.borrow(), vec!["tick 0", "tick -10", "end true"]);
+    }
+}
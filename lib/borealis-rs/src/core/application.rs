@@ -1,33 +1,74 @@
-use crate::core::activity::{Activity, ActivityDyn};
+use crate::core::activity::Activity;
+use crate::core::animation::update_highlight_animation;
 use crate::core::frame_context::FrameContext;
+use crate::core::geometry::Point;
+use crate::core::gesture::{dispatch_touch, interrupt_gestures, TouchPhase, TouchState};
 use crate::core::global::{
     set_content_height, set_content_width, set_window_height, set_window_scale, set_window_width,
     window_height, window_scale, window_width,
 };
-use crate::core::view_base::{BaseView, View};
-use crate::core::view_box::BoxView;
+#[cfg(feature = "hot-reload")]
+use crate::core::hot_reload::XMLWatcher;
+use crate::core::input::{
+    ButtonRepeater, ControllerButton, ControllerState, InputBackend, KeyboardMapping,
+    INPUT_CONFIG_FILE,
+};
+use crate::core::recording::{InputEvent, InputPlayer, InputRecorder, InputRecording};
+use crate::core::resource::resources_path;
+use crate::core::time::{Clock, ManualClock, RealClock, TickingScheduler, Time};
+use crate::core::transition::{reverse_transition, ActivityTransition, TransitionFrame};
+use crate::core::view_base::{FocusDirection, TransitionAnimation, View, ViewBase};
+use crate::core::view_box::{BoxEnum, BoxTrait};
+use crate::core::view_creator::{XMLViewCreator, XMLViewRegistry};
 use crate::core::view_drawer::ViewDrawer;
+use crate::core::view_layout::ViewLayout;
 use crate::core::{gl, GlWindow};
 use glutin::prelude::{GlSurface, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentGlContext};
 use glutin::surface::SwapInterval;
 use nanovg::{Color, PathOptions};
 use nanovg_sys::{
-    nvgBeginFrame, nvgBeginPath, nvgEndFrame, nvgFill, nvgFillColor, nvgRGB, nvgRGBA, nvgRect,
+    nvgBeginFrame, nvgBeginPath, nvgEndFrame, nvgFill, nvgFillColor, nvgGlobalAlpha, nvgRGB,
+    nvgRGBA, nvgRect, nvgRestore, nvgSave, nvgTranslate,
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::c_float;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use winit::dpi::{LogicalSize, PhysicalSize};
-use winit::event::{Event, WindowEvent};
+use winit::event::{
+    self, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, Touch, WindowEvent,
+};
 use winit::event_loop::EventLoop;
 use winit::window::WindowBuilder;
 
 const ORIGINAL_WINDOW_WIDTH: u32 = 1280;
 const ORIGINAL_WINDOW_HEIGHT: u32 = 720;
 
-pub type XMLViewCreator = Box<dyn Fn() -> BaseView>;
+/// Time between two frames while the only thing moving is the highlight pulsation.
+const HIGHLIGHT_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Time between two polls of the input backends while nothing else happens.
+const INPUT_BACKENDS_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Pixels scrolled with a touchpad making one line of the mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 40.0;
+
+/// Time in microseconds a replay goes on after its last event, at most,
+/// for the animations it started to end.
+const REPLAY_SETTLE_DURATION: Time = 5_000_000;
+
+/// Finger id given to the mouse, which acts as a finger while its left button is pressed.
+const MOUSE_FINGER_ID: u64 = u64::MAX;
+
+/// A finger currently on the screen.
+struct ActiveTouch {
+    first_responder: Rc<RefCell<View>>,
+    position: Point,
+}
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum InputType {
@@ -37,14 +78,33 @@ pub enum InputType {
 
 pub struct Application {
     title: String,
-    current_focus: Option<bool>,
-    start_time: i64,
-    frane_start_time: i64,
+    current_focus: Option<Rc<RefCell<View>>>,
+    clock: Box<dyn Clock>,
+    start_time: Time,
+    frane_start_time: Time,
     frame_index: u64,
     global_fps: u64,
-    views_to_draw: Vec<Rc<RefCell<View>>>,
     activities_stack: Vec<Rc<RefCell<Activity>>>,
-    focus_stack: Vec<Rc<RefCell<View>>>,
+    /// Focus of the application when each activity of the stack was pushed
+    focus_stack: Vec<Option<Rc<RefCell<View>>>>,
+    /// Transition each activity of the stack was pushed with
+    transition_stack: Vec<TransitionAnimation>,
+    /// Running transition between the two top activities, the input is blocked meanwhile
+    transition: Option<ActivityTransition>,
+    xml_views: XMLViewRegistry,
+    keyboard_mapping: KeyboardMapping,
+    input_backends: Vec<Box<dyn InputBackend>>,
+    button_repeater: ButtonRepeater,
+    touches: HashMap<u64, ActiveTouch>,
+    cursor_position: Point,
+    scroll_lines: Point,
+    recorder: Option<InputRecorder>,
+    recording_path: Option<PathBuf>,
+    player: Option<InputPlayer>,
+    clock_before_replay: Option<Box<dyn Clock>>,
+    tickings: TickingScheduler,
+    #[cfg(feature = "hot-reload")]
+    xml_watcher: XMLWatcher,
 }
 
 impl Application {
@@ -53,29 +113,60 @@ impl Application {
      * Returns Ok if it succeeded, Err otherwise.
      */
     pub fn init(title: &str) -> anyhow::Result<(Self, EventLoop<()>)> {
-        let now = chrono::Local::now().timestamp_nanos();
+        Ok((Self::new(title)?, EventLoop::new()))
+    }
+
+    /**
+     * Creates the application without its event loop, to run
+     * frames without any window (see run_replay()).
+     */
+    pub fn new(title: &str) -> anyhow::Result<Self> {
+        let clock = Box::new(RealClock);
+        let now = clock.now_usec();
         unsafe {
             // Init yoga
             let default_config = yoga_sys::YGConfigGetDefault();
             yoga_sys::YGConfigSetUseWebDefaults(default_config, true);
         }
-        Ok((
-            Application {
-                title: title.into(),
-                current_focus: None,
-                start_time: now,
-                frane_start_time: now,
-                frame_index: 0,
-                global_fps: 0,
-                views_to_draw: vec![],
-                activities_stack: vec![],
-                focus_stack: vec![],
-            },
-            EventLoop::new(),
-        ))
+        let tickings = TickingScheduler::new();
+        tickings.make_current();
+
+        let input_config = resources_path().join(INPUT_CONFIG_FILE);
+        let keyboard_mapping = if input_config.exists() {
+            KeyboardMapping::load(&input_config)?
+        } else {
+            KeyboardMapping::default()
+        };
+        Ok(Application {
+            title: title.into(),
+            current_focus: None,
+            clock,
+            start_time: now,
+            frane_start_time: now,
+            frame_index: 0,
+            global_fps: 0,
+            activities_stack: vec![],
+            focus_stack: vec![],
+            transition_stack: vec![],
+            transition: None,
+            xml_views: XMLViewRegistry::new(),
+            keyboard_mapping,
+            input_backends: vec![],
+            button_repeater: ButtonRepeater::default(),
+            touches: HashMap::new(),
+            cursor_position: Point::default(),
+            scroll_lines: Point::default(),
+            recorder: None,
+            recording_path: None,
+            player: None,
+            clock_before_replay: None,
+            tickings,
+            #[cfg(feature = "hot-reload")]
+            xml_watcher: XMLWatcher::new(),
+        })
     }
 
-    pub fn main_loop(self, event_loop: EventLoop<()>) {
+    pub fn main_loop(mut self, event_loop: EventLoop<()>) {
         let mut state = None;
         let mut frame_context = None;
         // Create a window with a default size and position
@@ -88,7 +179,6 @@ impl Application {
             );
 
         event_loop.run(move |event, event_loop_window_target, control_flow| {
-            control_flow.set_wait();
             // info!("{:?}", event);
             match event {
                 Event::Resumed => {
@@ -184,8 +274,78 @@ impl Application {
                             }
                         }
                     }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    } => {
+                        self.handle_live_input(InputEvent::Key(format!("{:?}", key)));
+                    }
+                    WindowEvent::Touch(Touch {
+                        id,
+                        phase,
+                        location,
+                        ..
+                    }) => {
+                        let phase = match phase {
+                            event::TouchPhase::Started => TouchPhase::Start,
+                            event::TouchPhase::Moved => TouchPhase::Stay,
+                            event::TouchPhase::Ended => TouchPhase::End,
+                            event::TouchPhase::Cancelled => {
+                                self.handle_live_input(InputEvent::TouchCancelled {
+                                    finger_id: id,
+                                });
+                                return;
+                            }
+                        };
+                        self.handle_live_input(InputEvent::Touch {
+                            finger_id: id,
+                            phase,
+                            position: Point::new(location.x as f32, location.y as f32),
+                        });
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        self.cursor_position = Point::new(position.x as f32, position.y as f32);
+                        if self.touches.contains_key(&MOUSE_FINGER_ID) {
+                            self.handle_live_input(InputEvent::Touch {
+                                finger_id: MOUSE_FINGER_ID,
+                                phase: TouchPhase::Stay,
+                                position: self.cursor_position,
+                            });
+                        }
+                    }
+                    WindowEvent::MouseInput {
+                        state,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        let phase = match state {
+                            ElementState::Pressed => TouchPhase::Start,
+                            ElementState::Released => TouchPhase::End,
+                        };
+                        self.handle_live_input(InputEvent::Touch {
+                            finger_id: MOUSE_FINGER_ID,
+                            phase,
+                            position: self.cursor_position,
+                        });
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let delta = match delta {
+                            MouseScrollDelta::LineDelta(x, y) => Point::new(x, y),
+                            MouseScrollDelta::PixelDelta(position) => Point::new(
+                                position.x as f32 / SCROLL_LINE_HEIGHT,
+                                position.y as f32 / SCROLL_LINE_HEIGHT,
+                            ),
+                        };
+                        self.handle_live_input(InputEvent::Scroll { delta });
+                    }
                     WindowEvent::CloseRequested => {
                         trace!("Event::WindowEvent::CloseRequested");
+                        self.save_recording();
                         control_flow.set_exit();
                     }
                     _ => {
@@ -208,6 +368,19 @@ impl Application {
                     }
                 }
                 Event::MainEventsCleared => {
+                    #[cfg(feature = "hot-reload")]
+                    self.reload_changed_activities();
+
+                    self.internal_main_loop();
+
+                    // Chosen here only: winit reads the control flow once
+                    // the redraw events following this one are handled
+                    let now = Instant::now();
+                    match self.next_wake_up(now) {
+                        Some(wake_up) if wake_up <= now => control_flow.set_poll(),
+                        Some(wake_up) => control_flow.set_wait_until(wake_up),
+                        None => control_flow.set_wait(),
+                    }
                 }
                 _ => (),
             }
@@ -215,10 +388,30 @@ impl Application {
     }
 
     pub fn internal_main_loop(&mut self) {
+        self.update_replay();
         self.update_fps();
-        self.frane_start_time = chrono::Local::now().timestamp_nanos();
+        self.frane_start_time = self.clock.now_usec();
+
+        self.poll_input_backends();
 
-        // Render
+        // Fingers staying on the screen, for gestures depending on time
+        let fingers: Vec<u64> = self.touches.keys().copied().collect();
+        for finger_id in fingers {
+            let position = self.touches[&finger_id].position;
+            self.on_touch(finger_id, TouchPhase::Stay, position);
+        }
+
+        // Animations
+        self.tickings.update_tickings(self.frane_start_time);
+        update_highlight_animation(self.frane_start_time);
+
+        if self
+            .transition
+            .as_ref()
+            .map_or(false, |transition| !transition.is_running())
+        {
+            self.end_transition();
+        }
     }
 
     pub fn frame(&self, ctx: &FrameContext, gl_window: &GlWindow) {
@@ -241,8 +434,38 @@ impl Application {
             nvgFillColor(ctx.vg().raw(), nvgRGB(255, 100, 0));
             nvgFill(ctx.vg().raw());
         }
-        for view in &self.views_to_draw {
-            view.borrow().frame(ctx);
+        for activity in self.visible_activities() {
+            let content_view = match &activity.borrow().view_data().content_view {
+                Some(content_view) => content_view.clone(),
+                None => continue,
+            };
+
+            let transition_frame = match &self.transition {
+                Some(transition) if Rc::ptr_eq(transition.top(), &activity) => {
+                    transition.top_frame(width as f32)
+                }
+                Some(transition) if Rc::ptr_eq(transition.bottom(), &activity) => {
+                    transition.bottom_frame(width as f32)
+                }
+                _ => TransitionFrame::IDENTITY,
+            };
+
+            // Global alpha also applies to the children of the content view
+            unsafe {
+                nvgSave(vg);
+                nvgTranslate(vg, transition_frame.offset_x, 0.0);
+                nvgGlobalAlpha(vg, transition_frame.alpha);
+            }
+            content_view.borrow().frame(ctx);
+            unsafe {
+                nvgRestore(vg);
+            }
+        }
+
+        // Draw the highlight of the focused view on top of everything,
+        // once the activity holding it stopped moving
+        if let (Some(focus), None) = (&self.current_focus, &self.transition) {
+            focus.borrow().frame_highlight(ctx);
         }
         unsafe {
             nvgEndFrame(ctx.vg().raw());
@@ -253,7 +476,7 @@ impl Application {
         self.frame_index += 1;
 
         // update FPS every second
-        if self.frane_start_time - self.start_time > 1000000000 {
+        if self.frane_start_time.saturating_sub(self.start_time) > 1000000 {
             self.global_fps = self.frame_index;
             self.start_time = self.frane_start_time;
             self.frame_index = 0;
@@ -261,16 +484,813 @@ impl Application {
         }
     }
 
-    pub fn register_xml_view(&self, name: &str, creator: XMLViewCreator) {}
+    /**
+     * Replaces the clock the application reads the time from,
+     * for instance by a ManualClock to run reproducible animations.
+     */
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        let now = clock.now_usec();
+        self.clock = clock;
+        self.start_time = now;
+        self.frane_start_time = now;
+        self.frame_index = 0;
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /**
+     * Registers a view to be used in XML files under the given tag name.
+     * Fails if a view is already registered with that name.
+     */
+    pub fn register_xml_view(&mut self, name: &str, creator: XMLViewCreator) -> anyhow::Result<()> {
+        self.xml_views.register(name, creator)
+    }
+
+    pub fn xml_views(&self) -> &XMLViewRegistry {
+        &self.xml_views
+    }
+
+    /**
+     * Replaces the mapping used to turn keyboard keys into controller buttons.
+     */
+    pub fn set_keyboard_mapping(&mut self, mapping: KeyboardMapping) {
+        self.keyboard_mapping = mapping;
+    }
+
+    pub fn keyboard_mapping(&self) -> &KeyboardMapping {
+        &self.keyboard_mapping
+    }
+
+    /**
+     * Handles an input event. Events from the window and replayed ones
+     * all go through here, so that replays behave like the recorded session.
+     * Events received during an activity transition are ignored.
+     */
+    pub fn handle_input(&mut self, event: InputEvent) {
+        // Keys are handled and recorded as the button they are mapped to,
+        // replays do not depend on the keyboard mapping
+        let event = match event {
+            InputEvent::Key(key) => {
+                let button = match self.keyboard_mapping.button_by_name(&key) {
+                    Some(button) => button,
+                    None => return,
+                };
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.record_key(self.clock.now_usec(), button, key);
+                }
+                InputEvent::Button(button)
+            }
+            event => {
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.record(self.clock.now_usec(), event.clone());
+                }
+                event
+            }
+        };
+
+        // Activities do not receive any input while they move
+        if self.is_transitioning() {
+            return;
+        }
+
+        match event {
+            InputEvent::Button(button) => {
+                set_input_type(InputType::GAMEPAD);
+                self.on_button_pressed(button);
+            }
+            // Turned into a button above
+            InputEvent::Key(_) => {}
+            InputEvent::Touch {
+                finger_id,
+                phase,
+                position,
+            } => self.on_touch(finger_id, phase, position),
+            InputEvent::TouchCancelled { finger_id } => self.cancel_touch(finger_id),
+            InputEvent::Scroll { delta } => self.on_scroll(delta),
+        }
+    }
+
+    /**
+     * Handles an event from the window, unless a recording is being replayed.
+     */
+    fn handle_live_input(&mut self, event: InputEvent) {
+        if !self.is_replaying() {
+            self.handle_input(event);
+        }
+    }
+
+    /**
+     * Moves the focus by one view for each line scrolled with the mouse wheel.
+     */
+    fn on_scroll(&mut self, delta: Point) {
+        set_input_type(InputType::GAMEPAD);
+        self.scroll_lines.x += delta.x;
+        self.scroll_lines.y += delta.y;
+
+        while self.scroll_lines.y >= 1.0 {
+            self.scroll_lines.y -= 1.0;
+            self.navigate(FocusDirection::Up);
+        }
+        while self.scroll_lines.y <= -1.0 {
+            self.scroll_lines.y += 1.0;
+            self.navigate(FocusDirection::Down);
+        }
+        while self.scroll_lines.x >= 1.0 {
+            self.scroll_lines.x -= 1.0;
+            self.navigate(FocusDirection::Right);
+        }
+        while self.scroll_lines.x <= -1.0 {
+            self.scroll_lines.x += 1.0;
+            self.navigate(FocusDirection::Left);
+        }
+    }
+
+    /**
+     * Starts recording the input events handled by the application, see stop_recording().
+     */
+    pub fn start_recording(&mut self) {
+        let window_size = (window_width(), window_height());
+        self.recorder = Some(InputRecorder::new(self.clock.now_usec(), Some(window_size)));
+    }
+
+    /**
+     * Starts recording, the recording is saved to the given file when the window is closed.
+     */
+    pub fn record_input_to(&mut self, path: PathBuf) {
+        self.start_recording();
+        self.recording_path = Some(path);
+    }
+
+    /**
+     * Stops recording and returns what has been recorded, if recording.
+     */
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.recorder.take().map(InputRecorder::finish)
+    }
+
+    fn save_recording(&mut self) {
+        let path = match self.recording_path.take() {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Some(recording) = self.stop_recording() {
+            match recording.save(&path) {
+                Ok(()) => info!("input recording saved to {:?}", path),
+                Err(e) => error!("{:#}", e),
+            }
+        }
+    }
+
+    /**
+     * Replays the given recording: its events are handled as if they came
+     * from the window, at their recorded time on a virtual clock moving
+     * by one frame each time internal_main_loop() is called.
+     * Input from the window is ignored until the replay is over.
+     */
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        if let Some((width, height)) = recording.window_size {
+            self.set_window_size(width, height);
+        }
+
+        // Fingers on the screen were not there when recording
+        self.cancel_touches();
+
+        let clock = ManualClock::new(self.clock.now_usec());
+        let previous_clock = std::mem::replace(&mut self.clock, Box::new(clock.clone()));
+        self.clock_before_replay.get_or_insert(previous_clock);
+        self.player = Some(InputPlayer::new(recording, clock));
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.player.is_some()
+    }
 
-    pub fn push_activity(&mut self, mut activity: Activity) {
+    /**
+     * Runs the frames of the replay until it is over, without drawing them.
+     * Used to replay a recording headless, on CI for instance.
+     */
+    pub fn run_replay(&mut self) {
+        while self.is_replaying() {
+            self.internal_main_loop();
+        }
+    }
+
+    /**
+     * Moves the replay to the next frame, handling each event received
+     * before it at its own time. The replay is over once every event
+     * has been handled and the animations they started have ended.
+     */
+    fn update_replay(&mut self) {
+        // Taken out of the application while its events are handled
+        let mut player = match self.player.take() {
+            Some(player) => player,
+            None => return,
+        };
+
+        if player.is_finished()
+            && (!self.tickings.is_ticking()
+                || player.frame_time() >= player.recording().duration() + REPLAY_SETTLE_DURATION)
+        {
+            info!("replay finished");
+            if let Some(clock) = self.clock_before_replay.take() {
+                self.set_clock(clock);
+            }
+            return;
+        }
+
+        for recorded in player.next_frame() {
+            player.seek(recorded.time);
+            self.handle_input(recorded.event);
+        }
+        player.seek(player.frame_time());
+
+        self.player = Some(player);
+    }
+
+    /**
+     * Adds a source of controller input, polled once per frame.
+     * The buttons of every backend are handled as if they came from one controller.
+     */
+    pub fn add_input_backend(&mut self, backend: Box<dyn InputBackend>) {
+        self.input_backends.push(backend);
+    }
+
+    /**
+     * Sets the time in ms to hold a D-pad button of a controller before
+     * it repeats, and the time in ms between two repeats.
+     */
+    pub fn set_button_repeat(&mut self, delay: Time, interval: Time) {
+        self.button_repeater.set_timing(delay, interval);
+    }
+
+    /**
+     * Handles the buttons pressed on the input backends since the previous frame.
+     * The left stick moves the focus like the D-pad.
+     */
+    fn poll_input_backends(&mut self) {
+        if self.input_backends.is_empty() {
+            return;
+        }
+
+        let mut unified_state = ControllerState::default();
+        for backend in &mut self.input_backends {
+            let mut state = ControllerState::default();
+            backend.update_controller_state(self.frane_start_time, &mut state);
+            unified_state.merge(&state);
+        }
+        unified_state.apply_left_stick_to_dpad();
+
+        for button in self
+            .button_repeater
+            .update(&unified_state, self.frane_start_time)
+        {
+            self.handle_live_input(InputEvent::Button(button));
+        }
+    }
+
+    /**
+     * Handles a controller button press, whatever the device it comes from.
+     * The actions of the focused view and its parents come first,
+     * then the D-pad moves the focus and B pops the top activity.
+     */
+    pub fn on_button_pressed(&mut self, button: ControllerButton) {
+        trace!("button pressed: {:?}", button);
+        if self.handle_action(button) {
+            return;
+        }
+
+        if let Some(direction) = button.focus_direction() {
+            self.navigate(direction);
+        }
+
+        // Back to the previous activity
+        if button == ControllerButton::B {
+            self.pop_activity();
+        }
+    }
+
+    /**
+     * Triggers the action registered on the given button, looking at the
+     * focused view then at its parents until an available action consumes it.
+     * Starts from the content view of the top activity if nothing is focused.
+     * Returns true if the button press was consumed.
+     */
+    fn handle_action(&mut self, button: ControllerButton) -> bool {
+        let mut current = self
+            .current_focus
+            .clone()
+            .or_else(|| self.top_content_view());
+        while let Some(view) = current {
+            let listener = view
+                .borrow()
+                .action(button)
+                .filter(|action| action.available)
+                .map(|action| action.listener.clone());
+
+            // The view is not borrowed anymore, the listener can change it
+            if let Some(listener) = listener {
+                if listener(view.clone()) {
+                    self.refresh_hints();
+                    return true;
+                }
+            }
+
+            current = view.borrow().parent();
+        }
+
+        false
+    }
+
+    fn top_content_view(&self) -> Option<Rc<RefCell<View>>> {
+        self.activities_stack
+            .last()
+            .and_then(|activity| activity.borrow().view_data().content_view.clone())
+    }
+
+    /**
+     * Refills the Hints views with the actions of the focused view.
+     * Called when the focus moves or after an action, which may
+     * have changed the actions.
+     */
+    pub fn refresh_hints(&self) {
+        let focus = self
+            .current_focus
+            .clone()
+            .or_else(|| self.top_content_view());
+        for activity in &self.activities_stack {
+            if let Some(view) = &activity.borrow().view_data().content_view {
+                refill_hints(view, &focus);
+            }
+        }
+    }
+
+    /**
+     * Handles a finger event, or a mouse event with the left button pressed.
+     * The view under the finger when it goes down receives all the
+     * events of that finger, until it goes up.
+     */
+    pub fn on_touch(&mut self, finger_id: u64, phase: TouchPhase, position: Point) {
+        let first_responder = match phase {
+            TouchPhase::Start => {
+                set_input_type(InputType::TOUCH);
+                let view = match self.top_content_view() {
+                    Some(root) => root.borrow().hit_test(&position),
+                    None => None,
+                };
+                match view {
+                    Some(view) => {
+                        self.touches.insert(
+                            finger_id,
+                            ActiveTouch {
+                                first_responder: view.clone(),
+                                position,
+                            },
+                        );
+                        view
+                    }
+                    None => return,
+                }
+            }
+            TouchPhase::Stay => match self.touches.get_mut(&finger_id) {
+                Some(touch) => {
+                    touch.position = position;
+                    touch.first_responder.clone()
+                }
+                None => return,
+            },
+            TouchPhase::End => match self.touches.remove(&finger_id) {
+                Some(touch) => touch.first_responder,
+                None => return,
+            },
+        };
+
+        let touch = TouchState {
+            finger_id,
+            phase,
+            position,
+            time: self.clock.now_usec(),
+        };
+        dispatch_touch(&first_responder, &touch);
+    }
+
+    /**
+     * Forgets a finger the system took away, interrupting its gestures.
+     */
+    pub fn cancel_touch(&mut self, finger_id: u64) {
+        if let Some(touch) = self.touches.remove(&finger_id) {
+            interrupt_gestures(&touch.first_responder, false);
+        }
+    }
+
+    fn cancel_touches(&mut self) {
+        for finger_id in self.touches.keys().copied().collect::<Vec<u64>>() {
+            self.cancel_touch(finger_id);
+        }
+    }
+
+    /**
+     * Pushes an activity on top of the stack with a fade, see push_activity_animated().
+     */
+    pub fn push_activity(&mut self, activity: Activity) -> anyhow::Result<()> {
+        self.push_activity_animated(activity, TransitionAnimation::Fade)
+    }
+
+    /**
+     * Pushes an activity on top of the stack. It takes the focus
+     * and the input from the activity below, which is paused.
+     * Both activities are animated with the given transition,
+     * the new one is resumed once the transition is over.
+     */
+    pub fn push_activity_animated(
+        &mut self,
+        mut activity: Activity,
+        animation: TransitionAnimation,
+    ) -> anyhow::Result<()> {
         warn!("push activity");
-        activity.set_content_view(activity.create_content_view());
+        let (content_view, xml_files) = activity.create_content_view_with_files(&self.xml_views);
+        activity.set_content_view(content_view?);
+        activity.view_data_mut().xml_files = xml_files;
         activity.on_content_available();
         activity.resize_to_fit_window();
-        self.views_to_draw
-            .push(activity.view_data().content_view.as_ref().unwrap().clone());
-        self.activities_stack.push(Rc::new(RefCell::new(activity)));
+        #[cfg(feature = "hot-reload")]
+        for file in &activity.view_data().xml_files {
+            self.xml_watcher.watch(file.clone());
+        }
+
+        // The previous transition jumps to its end
+        self.end_transition();
+
+        if let Some(last) = self.activities_stack.last() {
+            let last = last.borrow();
+            last.on_pause();
+            last.will_disappear(false);
+        }
+        self.cancel_touches();
+
+        // Remember the current focus to restore it when the activity is popped
+        self.focus_stack.push(self.current_focus.clone());
+        self.clear_focus();
+        activity.will_appear(true);
+        self.give_focus(activity.default_focus());
+
+        let activity = Rc::new(RefCell::new(activity));
+        let below = self.activities_stack.last().cloned();
+        self.activities_stack.push(activity.clone());
+        self.transition_stack.push(animation);
+        self.refresh_hints();
+
+        match below {
+            Some(below) => self.start_transition(animation, activity, below, true),
+            None => activity.borrow().on_resume(),
+        }
+        Ok(())
+    }
+
+    /**
+     * Pops the top activity with the reverse of the transition
+     * it was pushed with, see pop_activity_animated().
+     */
+    pub fn pop_activity(&mut self) -> bool {
+        let animation = match self.transition_stack.last() {
+            Some(animation) => reverse_transition(*animation),
+            None => return false,
+        };
+        self.pop_activity_animated(animation)
+    }
+
+    /**
+     * Pops the top activity and resumes the one below, giving the focus back
+     * to the view that had it when the popped activity was pushed.
+     * Both activities are animated with the given transition, the revealed
+     * one is resumed and the popped one destroyed once it is over.
+     * The last activity is never popped, returns false if there is nothing to pop.
+     */
+    pub fn pop_activity_animated(&mut self, animation: TransitionAnimation) -> bool {
+        if self.activities_stack.len() < 2 {
+            return false;
+        }
+
+        debug!("pop activity");
+        self.end_transition();
+
+        let activity = self.activities_stack.pop().unwrap();
+        self.transition_stack.pop();
+        self.cancel_touches();
+        {
+            let activity = activity.borrow();
+            activity.on_pause();
+            activity.will_disappear(true);
+        }
+
+        // The focus is somewhere in the popped activity
+        self.clear_focus();
+
+        let revealed = self.activities_stack.last().unwrap().clone();
+        revealed.borrow().will_appear(false);
+        let focus = self.focus_stack.pop().flatten();
+        let focus = focus.or_else(|| revealed.borrow().default_focus());
+        self.give_focus(focus);
+        self.refresh_hints();
+
+        self.start_transition(animation, activity, revealed, false);
+        true
+    }
+
+    /**
+     * Returns true while two activities are animated after a push or a pop.
+     */
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /**
+     * Starts the transition between the pushed or popped activity and the one below it.
+     * The one below fades as well if the top one is not translucent.
+     */
+    fn start_transition(
+        &mut self,
+        animation: TransitionAnimation,
+        top: Rc<RefCell<Activity>>,
+        bottom: Rc<RefCell<Activity>>,
+        pushing: bool,
+    ) {
+        let fade_bottom = !top.borrow().is_translucent();
+        self.transition = Some(ActivityTransition::new(
+            animation,
+            top,
+            bottom,
+            pushing,
+            fade_bottom,
+        ));
+
+        if animation == TransitionAnimation::None {
+            self.end_transition();
+        }
+    }
+
+    /**
+     * Ends the running transition, if any, resuming the top activity
+     * and destroying the popped one.
+     */
+    fn end_transition(&mut self) {
+        let transition = match self.transition.take() {
+            Some(transition) => transition,
+            None => return,
+        };
+
+        transition.stop();
+        if transition.is_pushing() {
+            transition.top().borrow().on_resume();
+        } else {
+            transition.bottom().borrow().on_resume();
+            transition.top().borrow().on_destroy();
+        }
+    }
+
+    /**
+     * Returns the activities that can be seen, from the bottom to the top:
+     * the top one and the ones below it, down to the first one which
+     * is not translucent. Both activities of a transition are seen.
+     */
+    fn visible_activities(&self) -> Vec<Rc<RefCell<Activity>>> {
+        let mut visible = vec![];
+        for activity in self.activities_stack.iter().rev() {
+            visible.insert(0, activity.clone());
+            if !activity.borrow().is_translucent() {
+                break;
+            }
+        }
+
+        match &self.transition {
+            Some(transition) if transition.is_pushing() => {
+                if !visible.iter().any(|a| Rc::ptr_eq(a, transition.bottom())) {
+                    visible.insert(0, transition.bottom().clone());
+                }
+            }
+            Some(transition) => visible.push(transition.top().clone()),
+            None => {}
+        }
+
+        visible
+    }
+
+    pub fn activities_stack(&self) -> &Vec<Rc<RefCell<Activity>>> {
+        &self.activities_stack
+    }
+
+    /**
+     * Removes the focus, from an activity that stops receiving the input.
+     */
+    fn clear_focus(&mut self) {
+        if let Some(focus) = self.current_focus.take() {
+            focus.borrow_mut().on_focus_lost();
+        }
+    }
+
+    /**
+     * Returns when the event loop has to run the next frame, now if something
+     * is animating, or None to wait for the next event.
+     * The pulsing highlight is fine with a lower frame rate.
+     */
+    fn next_wake_up(&self, now: Instant) -> Option<Instant> {
+        if self.tickings.is_ticking() || !self.touches.is_empty() || self.is_replaying() {
+            return Some(now);
+        }
+
+        let mut wake_ups = vec![];
+        if !self.input_backends.is_empty() {
+            wake_ups.push(now + INPUT_BACKENDS_POLL_INTERVAL);
+        }
+        if self.is_highlight_visible() {
+            wake_ups.push(now + HIGHLIGHT_FRAME_INTERVAL);
+        }
+        #[cfg(feature = "hot-reload")]
+        wake_ups.push(self.xml_watcher.next_check());
+
+        wake_ups.into_iter().min()
+    }
+
+    /**
+     * Returns true if the highlight of the focused view is drawn.
+     */
+    fn is_highlight_visible(&self) -> bool {
+        get_input_type() == InputType::GAMEPAD
+            && self.current_focus.as_ref().map_or(false, |focus| {
+                let focus = focus.borrow();
+                !focus.data().hide_highlight && !focus.data().hide_highlight_border
+            })
+    }
+
+    /**
+     * Returns the currently focused view.
+     */
+    pub fn current_focus(&self) -> Option<Rc<RefCell<View>>> {
+        self.current_focus.clone()
+    }
+
+    /**
+     * Gives the focus to the given view, or to its default focus
+     * if it cannot be focused itself (see ViewBase::default_focus()).
+     * Does nothing if neither the view nor its children can take the focus.
+     */
+    pub fn give_focus(&mut self, view: Option<Rc<RefCell<View>>>) {
+        let new_focus = match view.and_then(|view| view.borrow().default_focus()) {
+            Some(new_focus) => new_focus,
+            None => return,
+        };
+
+        if let Some(old_focus) = self.current_focus.take() {
+            if Rc::ptr_eq(&old_focus, &new_focus) {
+                self.current_focus = Some(old_focus);
+                return;
+            }
+            old_focus.borrow_mut().on_focus_lost();
+        }
+
+        // Every box on the way remembers which child holds the focus
+        let mut child = new_focus.clone();
+        let mut parent = child.borrow().parent();
+        while let Some(view) = parent {
+            if let View::Box(view) = &mut *view.borrow_mut() {
+                view.set_last_focused_view(Some(child.clone()));
+            }
+            parent = view.borrow().parent();
+            child = view;
+        }
+
+        new_focus.borrow_mut().on_focus_gained();
+        self.current_focus = Some(new_focus);
+        self.refresh_hints();
+    }
+
+    /**
+     * Moves the focus in the given direction. Each parent of the focused view
+     * is asked for the next view in turn, starting from the closest one.
+     * Shakes the highlight if there is nowhere to go.
+     */
+    pub fn navigate(&mut self, direction: FocusDirection) {
+        let current_focus = match &self.current_focus {
+            Some(current_focus) => current_focus.clone(),
+            None => return,
+        };
+
+        let mut next_focus = None;
+        let mut child = current_focus.clone();
+        let mut parent = current_focus.borrow().parent();
+        while let Some(view) = parent {
+            next_focus = view.borrow().next_focus(direction, &child);
+            if next_focus.is_some() {
+                break;
+            }
+            parent = view.borrow().parent();
+            child = view;
+        }
+
+        match next_focus {
+            Some(next_focus) => self.give_focus(Some(next_focus)),
+            None => current_focus.borrow_mut().shake_highlight(direction),
+        }
+    }
+
+    /**
+     * Inflates again the content view of the activities whose XML files
+     * (their own or an included one) changed on disk, and swaps it
+     * in place of the old one.
+     * The old view is kept if the new XML cannot be inflated.
+     */
+    #[cfg(feature = "hot-reload")]
+    fn reload_changed_activities(&mut self) {
+        let changed_files = self.xml_watcher.changed_files();
+        if changed_files.is_empty() {
+            return;
+        }
+
+        for index in 0..self.activities_stack.len() {
+            let activity = self.activities_stack[index].clone();
+            let file = match activity
+                .borrow()
+                .view_data()
+                .xml_files
+                .iter()
+                .find(|file| changed_files.contains(file))
+            {
+                Some(file) => file.clone(),
+                None => continue,
+            };
+
+            let (view, xml_files) = activity
+                .borrow()
+                .create_content_view_with_files(&self.xml_views);
+
+            // Includes may have been added or removed
+            for file in &xml_files {
+                self.xml_watcher.watch(file.clone());
+            }
+
+            let view = match view {
+                Ok(view) => view,
+                Err(e) => {
+                    error!("unable to reload {:?}: {:#}", file, e);
+                    // Files of the last working version are still tracked,
+                    // a broken file can drop includes that come back once fixed
+                    let mut activity = activity.borrow_mut();
+                    let tracked = &mut activity.view_data_mut().xml_files;
+                    for file in xml_files {
+                        if !tracked.contains(&file) {
+                            tracked.push(file);
+                        }
+                    }
+                    continue;
+                }
+            };
+            activity.borrow_mut().view_data_mut().xml_files = xml_files;
+
+            info!("reloaded {:?}", file);
+
+            let old_view = activity.borrow().view_data().content_view.clone();
+            {
+                let mut activity = activity.borrow_mut();
+                activity.set_content_view(view);
+                activity.on_content_available();
+                activity.resize_to_fit_window();
+            }
+
+            if let Some(old_view) = old_view {
+                self.forget_content_view(index, &old_view);
+            }
+        }
+    }
+
+    /**
+     * Forgets the content view of an activity replaced by hot reload:
+     * no focus is kept in the old view tree, and the new content view
+     * takes the focus if the activity is the top one.
+     */
+    #[cfg(feature = "hot-reload")]
+    fn forget_content_view(&mut self, index: usize, old_view: &Rc<RefCell<View>>) {
+        old_view.borrow().will_disappear(true);
+
+        // Restored on pop, the default focus is used instead
+        for focus in self.focus_stack.iter_mut() {
+            if focus
+                .as_ref()
+                .map_or(false, |focus| is_in_view_tree(focus, old_view))
+            {
+                *focus = None;
+            }
+        }
+
+        if index + 1 == self.activities_stack.len() {
+            let activity = self.activities_stack[index].clone();
+            self.clear_focus();
+            activity.borrow().will_appear(true);
+            let focus = activity.borrow().default_focus();
+            self.give_focus(focus);
+            self.refresh_hints();
+        }
     }
 
     pub fn set_window_size(&self, width: u32, height: u32) {
@@ -290,6 +1310,544 @@ impl Application {
     }
 }
 
+/**
+ * Returns true if the view is the given root view or one of its children, at any depth.
+ */
+#[cfg(feature = "hot-reload")]
+fn is_in_view_tree(view: &Rc<RefCell<View>>, root: &Rc<RefCell<View>>) -> bool {
+    let mut current = Some(view.clone());
+    while let Some(view) = current {
+        if Rc::ptr_eq(&view, root) {
+            return true;
+        }
+        current = view.borrow().parent();
+    }
+    false
+}
+
+/**
+ * Refills the Hints views found in the given view tree.
+ */
+fn refill_hints(view: &Rc<RefCell<View>>, focus: &Option<Rc<RefCell<View>>>) {
+    // Only the Hints view itself is borrowed mutably, the focused
+    // view and its parents are read while refilling
+    let children = match &*view.borrow() {
+        View::Box(BoxEnum::Hints(_)) => None,
+        View::Box(view) => Some(view.children().clone()),
+        _ => return,
+    };
+
+    match children {
+        Some(children) => {
+            for child in &children {
+                refill_hints(child, focus);
+            }
+        }
+        None => {
+            if let View::Box(BoxEnum::Hints(hints)) = &mut *view.borrow_mut() {
+                hints.refill(focus.clone());
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref INPUT_TYPE: Mutex<InputType> = Mutex::new(InputType::GAMEPAD);
+}
+
+/**
+ * Returns the kind of input the user used last.
+ */
 pub fn get_input_type() -> InputType {
-    InputType::TOUCH
+    *INPUT_TYPE.lock().unwrap()
+}
+
+pub fn set_input_type(input_type: InputType) {
+    let mut current = INPUT_TYPE.lock().unwrap();
+    if *current != input_type {
+        trace!("input type: {:?}", input_type);
+        *current = input_type;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        set_input_type, Application, InputType, HIGHLIGHT_FRAME_INTERVAL,
+        INPUT_BACKENDS_POLL_INTERVAL,
+    };
+    use crate::core::activity::{Activity, ActivityDyn, ActivityViewData};
+    use crate::core::input::ControllerButton;
+    use crate::core::recording::InputEvent;
+    use crate::core::time::{ManualClock, Ticking, Timer};
+    use crate::core::view_base::{FocusDirection, TransitionAnimation, View, ViewBase};
+    use crate::core::view_creator::{ViewCreator, XMLParser, XMLViewRegistry};
+    use crate::core::view_layout::ViewLayout;
+    use crate::core::virtual_gamepad::VirtualGamepad;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use std::time::Instant;
+
+    #[test]
+    fn wakes_up_at_once_only_while_ticking() {
+        let application = Application::new("test").unwrap();
+        let now = Instant::now();
+        #[cfg(not(feature = "hot-reload"))]
+        assert_eq!(application.next_wake_up(now), None);
+
+        let timer = Timer::new(100);
+        timer.start();
+        assert_eq!(application.next_wake_up(now), Some(now));
+
+        timer.stop();
+        assert_ne!(application.next_wake_up(now), Some(now));
+    }
+
+    #[test]
+    fn wakes_up_to_poll_the_input_backends() {
+        let mut application = Application::new("test").unwrap();
+        application.add_input_backend(Box::new(VirtualGamepad::new()));
+
+        // Without any window event, the gamepad is still polled
+        let now = Instant::now();
+        assert_eq!(
+            application.next_wake_up(now),
+            Some(now + INPUT_BACKENDS_POLL_INTERVAL)
+        );
+    }
+
+    #[test]
+    fn wakes_up_to_pulse_the_highlight() {
+        let mut application = Application::new("test").unwrap();
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Label focusable="true"/>"#;
+        let label = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        set_input_type(InputType::GAMEPAD);
+        application.give_focus(Some(label));
+        // Once the focus animation is over
+        while application.tickings.is_ticking() {
+            application.tickings.update(100);
+        }
+
+        let now = Instant::now();
+        assert_eq!(
+            application.next_wake_up(now),
+            Some(now + HIGHLIGHT_FRAME_INTERVAL)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hot-reload")]
+    fn wakes_up_to_check_the_xml_files() {
+        let application = Application::new("test").unwrap();
+        let now = Instant::now();
+        assert_eq!(
+            application.next_wake_up(now),
+            Some(application.xml_watcher.next_check())
+        );
+    }
+
+    /// Returns an application focused on a label inside a box, with the box
+    fn focused_label() -> (Application, Rc<RefCell<View>>, Rc<RefCell<View>>) {
+        let mut application = Application::new("test").unwrap();
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box>
+            <brls:Label id="label" focusable="true"/>
+        </brls:Box>"#;
+        let root = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        let label = root.borrow().get_view("label").unwrap();
+        application.give_focus(Some(label.clone()));
+        (application, root, label)
+    }
+
+    /// Registers an action on A recording its name and returning `consumed`
+    fn register(
+        view: &Rc<RefCell<View>>,
+        name: &'static str,
+        consumed: bool,
+        calls: &Rc<RefCell<Vec<&'static str>>>,
+    ) {
+        let calls = calls.clone();
+        view.borrow_mut().register_action(
+            name,
+            ControllerButton::A,
+            Box::new(move |_| {
+                calls.borrow_mut().push(name);
+                consumed
+            }),
+            false,
+        );
+    }
+
+    #[test]
+    fn action_bubbles_up_to_the_parents() {
+        let (mut application, root, _label) = focused_label();
+        let calls = Rc::new(RefCell::new(vec![]));
+        register(&root, "root", true, &calls);
+
+        assert!(application.handle_action(ControllerButton::A));
+        assert_eq!(*calls.borrow(), vec!["root"]);
+        assert!(!application.handle_action(ControllerButton::X));
+    }
+
+    #[test]
+    fn consumed_action_stops_the_bubbling() {
+        let (mut application, root, label) = focused_label();
+        let calls = Rc::new(RefCell::new(vec![]));
+        register(&root, "root", true, &calls);
+        register(&label, "label", true, &calls);
+
+        assert!(application.handle_action(ControllerButton::A));
+        assert_eq!(*calls.borrow(), vec!["label"]);
+    }
+
+    #[test]
+    fn declined_or_unavailable_action_goes_on_to_the_parents() {
+        let (mut application, root, label) = focused_label();
+        let calls = Rc::new(RefCell::new(vec![]));
+        register(&root, "root", false, &calls);
+        register(&label, "label", false, &calls);
+
+        assert!(!application.handle_action(ControllerButton::A));
+        assert_eq!(*calls.borrow(), vec!["label", "root"]);
+
+        calls.borrow_mut().clear();
+        label
+            .borrow_mut()
+            .set_action_available(ControllerButton::A, false);
+        assert!(!application.handle_action(ControllerButton::A));
+        assert_eq!(*calls.borrow(), vec!["root"]);
+    }
+
+    /// Returns an application focused on `a`, in a column of two rows: a b / c d
+    fn focus_grid() -> (Application, Rc<RefCell<View>>) {
+        let mut application = Application::new("test").unwrap();
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box axis="column">
+            <brls:Box axis="row">
+                <brls:Label id="a" focusable="true"/>
+                <brls:Label id="b" focusable="true"/>
+            </brls:Box>
+            <brls:Box axis="row">
+                <brls:Label id="c" focusable="true"/>
+                <brls:Label id="d" focusable="true"/>
+            </brls:Box>
+        </brls:Box>"#;
+        let root = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        application.give_focus(Some(root.clone()));
+        (application, root)
+    }
+
+    fn assert_focus(application: &Application, root: &Rc<RefCell<View>>, id: &str) {
+        let view = root.borrow().get_view(id).unwrap();
+        let focus = application.current_focus.clone().unwrap();
+        assert!(Rc::ptr_eq(&focus, &view), "the focus is not on {}", id);
+    }
+
+    #[test]
+    fn focus_moves_along_the_axis_of_the_box() {
+        let (mut application, root) = focus_grid();
+        assert_focus(&application, &root, "a");
+
+        application.navigate(FocusDirection::Right);
+        assert_focus(&application, &root, "b");
+        application.navigate(FocusDirection::Left);
+        assert_focus(&application, &root, "a");
+    }
+
+    #[test]
+    fn focus_leaves_a_box_through_its_parent() {
+        let (mut application, root) = focus_grid();
+
+        // The row cannot go down, the column moves to the next row
+        application.navigate(FocusDirection::Down);
+        assert_focus(&application, &root, "c");
+        application.navigate(FocusDirection::Up);
+        assert_focus(&application, &root, "a");
+    }
+
+    #[test]
+    fn focus_goes_back_to_the_last_focused_child() {
+        let (mut application, root) = focus_grid();
+        application.navigate(FocusDirection::Right);
+        application.navigate(FocusDirection::Down);
+        assert_focus(&application, &root, "c");
+        application.navigate(FocusDirection::Right);
+        assert_focus(&application, &root, "d");
+
+        application.navigate(FocusDirection::Up);
+        assert_focus(&application, &root, "b");
+        application.navigate(FocusDirection::Down);
+        assert_focus(&application, &root, "d");
+    }
+
+    #[test]
+    fn highlight_shakes_at_an_edge() {
+        let (mut application, root) = focus_grid();
+        for direction in [FocusDirection::Left, FocusDirection::Up] {
+            application.navigate(direction);
+            assert_focus(&application, &root, "a");
+
+            let focus = application.current_focus.clone().unwrap();
+            let focus = focus.borrow();
+            assert!(focus.data().highlight_shake.is_running());
+            assert_eq!(focus.data().highlight_shake_direction, direction);
+        }
+    }
+
+    /// Inflated from a string, records its lifecycle callbacks in `events`
+    struct TestActivity {
+        view_data: ActivityViewData,
+        name: &'static str,
+        xml: &'static str,
+        translucent: bool,
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TestActivity {
+        fn new(name: &'static str, xml: &'static str, events: &Rc<RefCell<Vec<String>>>) -> Self {
+            Self {
+                view_data: ActivityViewData::new(),
+                name,
+                xml,
+                translucent: false,
+                events: events.clone(),
+            }
+        }
+
+        fn record(&self, event: &str) {
+            self.events
+                .borrow_mut()
+                .push(format!("{} {}", self.name, event));
+        }
+    }
+
+    impl ViewCreator for TestActivity {}
+
+    impl ActivityDyn for TestActivity {
+        fn view_data(&self) -> &ActivityViewData {
+            &self.view_data
+        }
+
+        fn view_data_mut(&mut self) -> &mut ActivityViewData {
+            &mut self.view_data
+        }
+
+        fn create_content_view_with_files(
+            &self,
+            registry: &XMLViewRegistry,
+        ) -> (anyhow::Result<Rc<RefCell<View>>>, Vec<PathBuf>) {
+            (
+                self.create_from_xml_string(registry, self.xml.into()),
+                vec![],
+            )
+        }
+
+        fn is_translucent(&self) -> bool {
+            self.translucent
+        }
+
+        fn on_resume(&self) {
+            self.record("resume");
+        }
+
+        fn on_pause(&self) {
+            self.record("pause");
+        }
+
+        fn on_destroy(&self) {
+            self.record("destroy");
+        }
+    }
+
+    const ROW: &str = r#"<brls:Box axis="row">
+        <brls:Label id="first" focusable="true"/>
+        <brls:Label id="second" focusable="true"/>
+    </brls:Box>"#;
+
+    fn push(application: &mut Application, activity: TestActivity) {
+        application
+            .push_activity_animated(Box::new(activity), TransitionAnimation::None)
+            .unwrap();
+    }
+
+    fn focused_id(application: &Application) -> String {
+        let focus = application.current_focus.clone().unwrap();
+        let id = focus.borrow().data().id.clone();
+        id
+    }
+
+    #[test]
+    fn popping_gives_the_focus_back() {
+        let mut application = Application::new("test").unwrap();
+        let events = Rc::new(RefCell::new(vec![]));
+        push(&mut application, TestActivity::new("main", ROW, &events));
+        application.navigate(FocusDirection::Right);
+        assert_eq!(focused_id(&application), "second");
+
+        let dialog = r#"<brls:Label id="dialog" focusable="true"/>"#;
+        push(
+            &mut application,
+            TestActivity::new("dialog", dialog, &events),
+        );
+        assert_eq!(focused_id(&application), "dialog");
+
+        assert!(application.pop_activity());
+        assert_eq!(focused_id(&application), "second");
+    }
+
+    #[test]
+    fn root_activity_is_never_popped() {
+        let mut application = Application::new("test").unwrap();
+        assert!(!application.pop_activity());
+
+        let events = Rc::new(RefCell::new(vec![]));
+        push(&mut application, TestActivity::new("main", ROW, &events));
+        assert!(!application.pop_activity());
+        assert!(!application.pop_activity_animated(TransitionAnimation::None));
+        assert_eq!(application.activities_stack.len(), 1);
+    }
+
+    #[test]
+    fn b_pops_only_if_no_action_consumed_it() {
+        let mut application = Application::new("test").unwrap();
+        let events = Rc::new(RefCell::new(vec![]));
+        push(&mut application, TestActivity::new("main", ROW, &events));
+        push(&mut application, TestActivity::new("dialog", ROW, &events));
+
+        let consumed = Rc::new(RefCell::new(true));
+        let focus = application.current_focus.clone().unwrap();
+        let listener_consumed = consumed.clone();
+        focus.borrow_mut().register_action(
+            "Close",
+            ControllerButton::B,
+            Box::new(move |_| *listener_consumed.borrow()),
+            false,
+        );
+
+        application.on_button_pressed(ControllerButton::B);
+        assert_eq!(application.activities_stack.len(), 2);
+
+        *consumed.borrow_mut() = false;
+        application.on_button_pressed(ControllerButton::B);
+        assert_eq!(application.activities_stack.len(), 1);
+    }
+
+    /// Returns an application running on a manual clock, showing one activity
+    fn application_with_clock() -> (Application, ManualClock, Rc<RefCell<Vec<String>>>) {
+        let mut application = Application::new("test").unwrap();
+        let clock = ManualClock::new(1_000_000);
+        application.set_clock(Box::new(clock.clone()));
+        let events = Rc::new(RefCell::new(vec![]));
+        push(&mut application, TestActivity::new("main", ROW, &events));
+        events.borrow_mut().clear();
+        (application, clock, events)
+    }
+
+    /// Runs frames until the transition is over, returns how many
+    fn run_transition(application: &mut Application, clock: &ManualClock) -> u32 {
+        let mut frames = 0;
+        while application.is_transitioning() {
+            assert!(frames < 1000, "the transition never ends");
+            clock.advance(16);
+            application.internal_main_loop();
+            frames += 1;
+        }
+        frames
+    }
+
+    #[test]
+    fn pushed_activity_is_resumed_once_the_transition_is_over() {
+        let (mut application, clock, events) = application_with_clock();
+        application
+            .push_activity(Box::new(TestActivity::new("dialog", ROW, &events)))
+            .unwrap();
+        assert!(application.is_transitioning());
+        assert_eq!(*events.borrow(), vec!["main pause"]);
+
+        assert!(run_transition(&mut application, &clock) > 1);
+        assert_eq!(*events.borrow(), vec!["main pause", "dialog resume"]);
+    }
+
+    #[test]
+    fn popped_activity_is_destroyed_after_the_revealed_one_is_resumed() {
+        let (mut application, clock, events) = application_with_clock();
+        push(&mut application, TestActivity::new("dialog", ROW, &events));
+        events.borrow_mut().clear();
+
+        assert!(application.pop_activity_animated(TransitionAnimation::Fade));
+        assert!(application.is_transitioning());
+        assert_eq!(*events.borrow(), vec!["dialog pause"]);
+
+        run_transition(&mut application, &clock);
+        assert_eq!(
+            *events.borrow(),
+            vec!["dialog pause", "main resume", "dialog destroy"]
+        );
+    }
+
+    #[test]
+    fn input_is_dropped_during_a_transition() {
+        let (mut application, clock, events) = application_with_clock();
+        application
+            .push_activity(Box::new(TestActivity::new("dialog", ROW, &events)))
+            .unwrap();
+
+        application.handle_input(InputEvent::Button(ControllerButton::Right));
+        application.handle_input(InputEvent::Button(ControllerButton::B));
+        assert_eq!(focused_id(&application), "first");
+        assert_eq!(application.activities_stack.len(), 2);
+
+        run_transition(&mut application, &clock);
+        application.handle_input(InputEvent::Button(ControllerButton::Right));
+        assert_eq!(focused_id(&application), "second");
+        application.handle_input(InputEvent::Button(ControllerButton::B));
+        assert_eq!(application.activities_stack.len(), 1);
+    }
+
+    fn visible_ids(application: &Application) -> Vec<String> {
+        let id = |activity: &Rc<RefCell<Activity>>| {
+            let activity = activity.borrow();
+            let view = activity.view_data().content_view.clone().unwrap();
+            let id = view.borrow().data().id.clone();
+            id
+        };
+        application.visible_activities().iter().map(id).collect()
+    }
+
+    #[test]
+    fn activities_are_visible_down_to_the_first_opaque_one() {
+        let (mut application, clock, events) = application_with_clock();
+        let activity = |xml, translucent| TestActivity {
+            translucent,
+            ..TestActivity::new("test", xml, &events)
+        };
+        push(&mut application, activity(r#"<brls:Box id="a"/>"#, false));
+        push(&mut application, activity(r#"<brls:Box id="b"/>"#, true));
+        push(&mut application, activity(r#"<brls:Box id="c"/>"#, true));
+        assert_eq!(visible_ids(&application), vec!["a", "b", "c"]);
+
+        push(&mut application, activity(r#"<brls:Box id="d"/>"#, false));
+        assert_eq!(visible_ids(&application), vec!["d"]);
+
+        // Both activities of a transition are seen
+        application
+            .push_activity(Box::new(activity(r#"<brls:Box id="e"/>"#, false)))
+            .unwrap();
+        assert_eq!(visible_ids(&application), vec!["d", "e"]);
+        run_transition(&mut application, &clock);
+        assert_eq!(visible_ids(&application), vec!["e"]);
+
+        application.pop_activity();
+        assert_eq!(visible_ids(&application), vec!["d", "e"]);
+        run_transition(&mut application, &clock);
+        assert_eq!(visible_ids(&application), vec!["d"]);
+    }
 }
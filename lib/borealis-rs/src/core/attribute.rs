@@ -0,0 +1,466 @@
+use crate::core::resource::{
+    resolve_i18n, resolve_res, resolve_style, resolve_theme, ResourceReference,
+};
+use crate::core::theme::{nvg_rgba, AUTO};
+use crate::core::view_base::View;
+use nanovg_sys::NVGcolor;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub type AttributeSetter<T> = Box<dyn Fn(&mut View, T)>;
+pub type AutoAttributeSetter = Box<dyn Fn(&mut View)>;
+pub type EnumAttributeSetter = Box<dyn Fn(&mut View, &str) -> Result<(), String>>;
+
+/// The XML attributes a view type accepts, each one with the setter to call.
+///
+/// The same attribute name can be registered with several kinds, for instance
+/// "width" accepts "auto", a number of pixels and a percentage. When applied,
+/// the value is matched against every kind registered for the name, in that order:
+/// auto, percentage, float, color, boolean, enum, file path and string.
+///
+/// Resource references are resolved first: "@style/" goes to float attributes,
+/// "@theme/" to colors, "@i18n/" to strings and "@res/" to file paths.
+#[derive(Default)]
+pub struct XMLAttributes {
+    auto: HashMap<String, AutoAttributeSetter>,
+    percentage: HashMap<String, AttributeSetter<f32>>,
+    float: HashMap<String, AttributeSetter<f32>>,
+    color: HashMap<String, AttributeSetter<NVGcolor>>,
+    bool: HashMap<String, AttributeSetter<bool>>,
+    enums: HashMap<String, EnumAttributeSetter>,
+    file_path: HashMap<String, AttributeSetter<PathBuf>>,
+    string: HashMap<String, Box<dyn Fn(&mut View, &str)>>,
+}
+
+impl XMLAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Registers an attribute accepting the "auto" value.
+     */
+    pub fn register_auto_attribute(&mut self, name: &str, setter: impl Fn(&mut View) + 'static) {
+        self.auto.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting a percentage ("NN%"). The setter receives
+     * the percentage between 0.0f and 100.0f.
+     */
+    pub fn register_percentage_attribute(
+        &mut self,
+        name: &str,
+        setter: impl Fn(&mut View, f32) + 'static,
+    ) {
+        self.percentage.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting a number or a @style/ reference.
+     */
+    pub fn register_float_attribute(&mut self, name: &str, setter: impl Fn(&mut View, f32) + 'static) {
+        self.float.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting a number, a @style/ reference or "auto".
+     * "auto" is given to the setter as AUTO.
+     */
+    pub fn register_auto_float_attribute(
+        &mut self,
+        name: &str,
+        setter: impl Fn(&mut View, f32) + 'static,
+    ) {
+        let setter = Rc::new(setter);
+        let auto_setter = setter.clone();
+        self.register_auto_attribute(name, move |view| auto_setter(view, AUTO));
+        self.register_float_attribute(name, move |view, value| setter(view, value));
+    }
+
+    /**
+     * Registers an attribute accepting a "#RRGGBB", "#RRGGBBAA" or @theme/ color.
+     */
+    pub fn register_color_attribute(
+        &mut self,
+        name: &str,
+        setter: impl Fn(&mut View, NVGcolor) + 'static,
+    ) {
+        self.color.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting "true" or "false".
+     */
+    pub fn register_bool_attribute(&mut self, name: &str, setter: impl Fn(&mut View, bool) + 'static) {
+        self.bool.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting one of the given names, each
+     * one mapped to a value of the enum.
+     */
+    pub fn register_enum_attribute<E: Copy + 'static>(
+        &mut self,
+        name: &str,
+        values: &'static [(&'static str, E)],
+        setter: impl Fn(&mut View, E) + 'static,
+    ) {
+        self.enums.insert(
+            name.into(),
+            Box::new(move |view, value| {
+                match values.iter().find(|(name, _)| *name == value) {
+                    Some((_, value)) => {
+                        setter(view, *value);
+                        Ok(())
+                    }
+                    None => Err(format!(
+                        "one of {}",
+                        values
+                            .iter()
+                            .map(|(name, _)| format!("\"{}\"", name))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                }
+            }),
+        );
+    }
+
+    /**
+     * Registers an attribute accepting a path to a file.
+     */
+    pub fn register_file_path_attribute(
+        &mut self,
+        name: &str,
+        setter: impl Fn(&mut View, PathBuf) + 'static,
+    ) {
+        self.file_path.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Registers an attribute accepting any string.
+     */
+    pub fn register_string_attribute(&mut self, name: &str, setter: impl Fn(&mut View, &str) + 'static) {
+        self.string.insert(name.into(), Box::new(setter));
+    }
+
+    /**
+     * Returns true if an attribute with the given name is registered, whatever its kind.
+     */
+    pub fn contains(&self, name: &str) -> bool {
+        self.auto.contains_key(name)
+            || self.percentage.contains_key(name)
+            || self.float.contains_key(name)
+            || self.color.contains_key(name)
+            || self.bool.contains_key(name)
+            || self.enums.contains_key(name)
+            || self.file_path.contains_key(name)
+            || self.string.contains_key(name)
+    }
+
+    /**
+     * Parses the value and calls the matching setter on the view.
+     * Returns a message describing the problem if the attribute is unknown
+     * or if its value cannot be parsed by any of the registered kinds.
+     */
+    pub fn apply(&self, view: &mut View, name: &str, value: &str) -> Result<(), String> {
+        if !self.contains(name) {
+            return Err(format!("unknown attribute \"{}\"", name));
+        }
+
+        if let Some(reference) = ResourceReference::parse(value) {
+            return self.apply_reference(view, name, value, reference);
+        }
+
+        let mut expected = vec![];
+
+        if let Some(setter) = self.auto.get(name) {
+            if value == "auto" {
+                setter(view);
+                return Ok(());
+            }
+            expected.push("\"auto\"".to_string());
+        }
+
+        if let Some(setter) = self.percentage.get(name) {
+            match parse_percentage(value) {
+                Some(Ok(percentage)) => {
+                    setter(view, percentage);
+                    return Ok(());
+                }
+                Some(Err(message)) => expected.push(message),
+                None => expected.push("a percentage".to_string()),
+            }
+        }
+
+        if let Some(setter) = self.float.get(name) {
+            match parse_float(value) {
+                Ok(float) => {
+                    setter(view, float);
+                    return Ok(());
+                }
+                Err(message) => expected.push(message),
+            }
+        }
+
+        if let Some(setter) = self.color.get(name) {
+            match parse_color(value) {
+                Ok(color) => {
+                    setter(view, color);
+                    return Ok(());
+                }
+                Err(message) => expected.push(message),
+            }
+        }
+
+        if let Some(setter) = self.bool.get(name) {
+            match parse_bool(value) {
+                Ok(bool) => {
+                    setter(view, bool);
+                    return Ok(());
+                }
+                Err(message) => expected.push(message),
+            }
+        }
+
+        if let Some(setter) = self.enums.get(name) {
+            match setter(view, value) {
+                Ok(()) => return Ok(()),
+                Err(message) => expected.push(message),
+            }
+        }
+
+        if let Some(setter) = self.file_path.get(name) {
+            setter(view, PathBuf::from(value));
+            return Ok(());
+        }
+
+        if let Some(setter) = self.string.get(name) {
+            setter(view, value);
+            return Ok(());
+        }
+
+        Err(format!(
+            "invalid value \"{}\" for attribute \"{}\", expected {}",
+            value,
+            name,
+            expected.join(" or ")
+        ))
+    }
+
+    fn apply_reference(
+        &self,
+        view: &mut View,
+        name: &str,
+        value: &str,
+        reference: ResourceReference,
+    ) -> Result<(), String> {
+        let unsupported = || {
+            format!(
+                "attribute \"{}\" does not accept \"{}\" references",
+                name,
+                value.split('/').next().unwrap_or(value)
+            )
+        };
+
+        match reference {
+            ResourceReference::Style(_) => {
+                let setter = self.float.get(name).ok_or_else(unsupported)?;
+                setter(view, resolve_style(value).map_err(|e| e.to_string())?);
+            }
+            ResourceReference::Theme(_) => {
+                let setter = self.color.get(name).ok_or_else(unsupported)?;
+                setter(view, resolve_theme(value).map_err(|e| e.to_string())?);
+            }
+            ResourceReference::I18n(_) => {
+                let setter = self.string.get(name).ok_or_else(unsupported)?;
+                setter(view, &resolve_i18n(value).map_err(|e| e.to_string())?);
+            }
+            ResourceReference::Res(_) => {
+                let path = resolve_res(value).map_err(|e| e.to_string())?;
+                if let Some(setter) = self.file_path.get(name) {
+                    setter(view, path);
+                } else if let Some(setter) = self.string.get(name) {
+                    setter(view, &path.to_string_lossy());
+                } else {
+                    return Err(unsupported());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses "NN%", returning None if the value is not a percentage at all.
+pub fn parse_percentage(value: &str) -> Option<Result<f32, String>> {
+    value.strip_suffix('%').map(|percentage| {
+        percentage
+            .trim()
+            .parse()
+            .map_err(|_| format!("a valid percentage instead of \"{}\"", value))
+    })
+}
+
+/// Parses a finite number, "NaN" and "inf" are not accepted.
+pub fn parse_float(value: &str) -> Result<f32, String> {
+    match value.trim().parse::<f32>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err("a number".to_string()),
+    }
+}
+
+/// Parses "#RRGGBB" or "#RRGGBBAA".
+pub fn parse_color(value: &str) -> Result<NVGcolor, String> {
+    let expected = || "a color (#RRGGBB or #RRGGBBAA)".to_string();
+
+    let hex = value.strip_prefix('#').ok_or_else(expected)?;
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+        return Err(expected());
+    }
+
+    let channel = |index: usize| {
+        hex.get(index * 2..index * 2 + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .ok_or_else(expected)
+    };
+
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Ok(nvg_rgba(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+/// Parses "true" or "false".
+pub fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err("\"true\" or \"false\"".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::theme::theme;
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use crate::views::rectangle::Rectangle;
+    use std::cell::RefCell;
+
+    #[test]
+    fn percentages() {
+        assert_eq!(parse_percentage("50%"), Some(Ok(50.0)));
+        assert_eq!(parse_percentage("12.5 %"), Some(Ok(12.5)));
+        assert_eq!(
+            parse_percentage("half%"),
+            Some(Err("a valid percentage instead of \"half%\"".into()))
+        );
+        assert_eq!(parse_percentage("50"), None);
+    }
+
+    #[test]
+    fn colors() {
+        let rgb = parse_color("#ff8000").unwrap();
+        assert_eq!(rgb.rgba, nvg_rgba(255, 128, 0, 255).rgba);
+        let rgba = parse_color("#FF800080").unwrap();
+        assert_eq!(rgba.rgba, nvg_rgba(255, 128, 0, 128).rgba);
+
+        for value in ["ff8000", "#ff80", "#ff8000a", "#gg8000", "#ff80é0"] {
+            assert_eq!(
+                parse_color(value).err(),
+                Some("a color (#RRGGBB or #RRGGBBAA)".into()),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn floats_are_finite() {
+        assert_eq!(parse_float(" 12.5 "), Ok(12.5));
+        assert_eq!(parse_float("-3"), Ok(-3.0));
+        for value in ["NaN", "inf", "-infinity", "12px", ""] {
+            assert_eq!(parse_float(value), Err("a number".into()), "{}", value);
+        }
+    }
+
+    /// Applies the value to the "width" attribute, accepting auto, a percentage
+    /// or a number, or to the "color" one, returning what their setters received.
+    fn apply(name: &str, value: &str) -> Result<String, String> {
+        let received = Rc::new(RefCell::new(String::new()));
+        let mut attributes = XMLAttributes::new();
+        let auto = received.clone();
+        attributes.register_auto_float_attribute("width", move |_, value| {
+            *auto.borrow_mut() = match value.is_nan() {
+                true => "auto".into(),
+                false => format!("{}px", value),
+            }
+        });
+        let percentage = received.clone();
+        attributes.register_percentage_attribute("width", move |_, value| {
+            *percentage.borrow_mut() = format!("{}%", value)
+        });
+        let color = received.clone();
+        attributes.register_color_attribute("color", move |_, value| {
+            *color.borrow_mut() = format!("{:?}", value.rgba)
+        });
+
+        let mut view = View::Rectangle(Rectangle::new());
+        attributes.apply(&mut view, name, value)?;
+        let received = received.borrow().clone();
+        Ok(received)
+    }
+
+    #[test]
+    fn values_go_to_the_matching_kind() {
+        assert_eq!(apply("width", "auto"), Ok("auto".into()));
+        assert_eq!(apply("width", "50%"), Ok("50%".into()));
+        assert_eq!(apply("width", "120"), Ok("120px".into()));
+        assert_eq!(
+            apply("width", "wide"),
+            Err("invalid value \"wide\" for attribute \"width\", \
+                 expected \"auto\" or a percentage or a number"
+                .into())
+        );
+    }
+
+    #[test]
+    fn references_are_resolved() {
+        assert_eq!(
+            apply("width", "@style/brls/animations/show"),
+            Ok("200px".into())
+        );
+        assert_eq!(
+            apply("color", "@theme/brls/text"),
+            Ok(format!("{:?}", theme("brls/text").rgba))
+        );
+        assert_eq!(
+            apply("width", "@style/brls/unknown"),
+            Err("unresolved reference \"@style/brls/unknown\": unknown style".into())
+        );
+        assert_eq!(
+            apply("width", "@theme/brls/text"),
+            Err("attribute \"width\" does not accept \"@theme\" references".into())
+        );
+    }
+
+    #[test]
+    fn error_names_the_view_the_attribute_and_the_line() {
+        let registry = XMLViewRegistry::new();
+        let xml = "<brls:Box>\n    <brls:Label width=\"wide\"/>\n</brls:Box>";
+        let error = XMLParser::new(&registry, "main.xml".into(), xml)
+            .parse()
+            .err()
+            .unwrap();
+
+        let messages: Vec<String> = error.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "main.xml:2: <brls:Label>: invalid value \"wide\" for attribute \"width\", \
+                  expected \"auto\" or a percentage or a number"
+            ]
+        );
+    }
+}
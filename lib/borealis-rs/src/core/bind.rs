@@ -1,17 +1,381 @@
-use crate::core::view_drawer::ViewTrait;
+use crate::core::view_base::View;
+use crate::core::view_box::{BoxEnum, BoxView};
+use crate::core::view_layout::ViewLayout;
+use crate::views::applet_frame::AppletFrame;
+use crate::views::bottom_bar::BottomBar;
+use crate::views::button::Button;
+use crate::views::debug_layer::DebugLayer;
+use crate::views::dialog::Dialog;
+use crate::views::dropdown::Dropdown;
+use crate::views::edit_text_dialog::EditTextDialog;
+use crate::views::h_scrolling_frame::HScrollingFrame;
+use crate::views::header::Header;
+use crate::views::hint::{Hint, Hints};
+use crate::views::image::Image;
+use crate::views::label::Label;
+use crate::views::progress_spinner::ProgressSpinner;
+use crate::views::recycler::{RecyclerCell, RecyclerHeader};
+use crate::views::rectangle::Rectangle;
+use crate::views::scrolling_frame::ScrollingFrame;
+use crate::views::slider::Slider;
+use crate::views::tab_frame::TabFrame;
+use std::cell::{OnceCell, Ref, RefCell, RefMut};
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
 
-pub struct BoundView<T: ViewTrait> {
+pub use borealis_rs_derive::BindViews;
+
+/// A view type a BoundView can be downcast to.
+pub trait ViewCast: 'static {
+    /// Name of the type, used in error messages
+    const NAME: &'static str;
+
+    fn cast(view: &View) -> Option<&Self>;
+
+    fn cast_mut(view: &mut View) -> Option<&mut Self>;
+}
+
+impl ViewCast for View {
+    const NAME: &'static str = "View";
+
+    fn cast(view: &View) -> Option<&Self> {
+        Some(view)
+    }
+
+    fn cast_mut(view: &mut View) -> Option<&mut Self> {
+        Some(view)
+    }
+}
+
+impl ViewCast for BoxEnum {
+    const NAME: &'static str = "Box";
+
+    fn cast(view: &View) -> Option<&Self> {
+        match view {
+            View::Box(view) => Some(view),
+            _ => None,
+        }
+    }
+
+    fn cast_mut(view: &mut View) -> Option<&mut Self> {
+        match view {
+            View::Box(view) => Some(view),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_view_cast {
+    ($($variant:ident($view:ident) => $name:literal),* $(,)?) => {
+        $(
+            impl ViewCast for $view {
+                const NAME: &'static str = $name;
+
+                fn cast(view: &View) -> Option<&Self> {
+                    match view {
+                        View::$variant(view) => Some(view),
+                        _ => None,
+                    }
+                }
+
+                fn cast_mut(view: &mut View) -> Option<&mut Self> {
+                    match view {
+                        View::$variant(view) => Some(view),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_box_view_cast {
+    ($($variant:ident($view:ident) => $name:literal),* $(,)?) => {
+        $(
+            impl ViewCast for $view {
+                const NAME: &'static str = $name;
+
+                fn cast(view: &View) -> Option<&Self> {
+                    match view {
+                        View::Box(BoxEnum::$variant(view)) => Some(view),
+                        _ => None,
+                    }
+                }
+
+                fn cast_mut(view: &mut View) -> Option<&mut Self> {
+                    match view {
+                        View::Box(BoxEnum::$variant(view)) => Some(view),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_view_cast! {
+    Image(Image) => "Image",
+    Label(Label) => "Label",
+    ProgressSpinner(ProgressSpinner) => "ProgressSpinner",
+    Rectangle(Rectangle) => "Rectangle",
+}
+
+impl_box_view_cast! {
+    Box(BoxView) => "BoxView",
+    AppletFrame(AppletFrame) => "AppletFrame",
+    BottomBar(BottomBar) => "BottomBar",
+    Button(Button) => "Button",
+    DebugLayer(DebugLayer) => "DebugLayer",
+    Dialog(Dialog) => "Dialog",
+    Dropdown(Dropdown) => "Dropdown",
+    EditTextDialog(EditTextDialog) => "EditTextDialog",
+    HScrollingFrame(HScrollingFrame) => "HScrollingFrame",
+    Header(Header) => "Header",
+    Hint(Hint) => "Hint",
+    Hints(Hints) => "Hints",
+    RecyclerCell(RecyclerCell) => "RecyclerCell",
+    RecyclerHeader(RecyclerHeader) => "RecyclerHeader",
+    ScrollingFrame(ScrollingFrame) => "ScrollingFrame",
+    Slider(Slider) => "Slider",
+    TabFrame(TabFrame) => "TabFrame",
+}
+
+/// Returns the name of the concrete type of the view.
+fn view_type_name(view: &View) -> &'static str {
+    match view {
+        View::Box(view) => match view {
+            BoxEnum::Box(_) => BoxView::NAME,
+            BoxEnum::AppletFrame(_) => AppletFrame::NAME,
+            BoxEnum::BottomBar(_) => BottomBar::NAME,
+            BoxEnum::Button(_) => Button::NAME,
+            BoxEnum::DebugLayer(_) => DebugLayer::NAME,
+            BoxEnum::Dialog(_) => Dialog::NAME,
+            BoxEnum::Dropdown(_) => Dropdown::NAME,
+            BoxEnum::EditTextDialog(_) => EditTextDialog::NAME,
+            BoxEnum::HScrollingFrame(_) => HScrollingFrame::NAME,
+            BoxEnum::Header(_) => Header::NAME,
+            BoxEnum::Hint(_) => Hint::NAME,
+            BoxEnum::Hints(_) => Hints::NAME,
+            BoxEnum::RecyclerCell(_) => RecyclerCell::NAME,
+            BoxEnum::RecyclerHeader(_) => RecyclerHeader::NAME,
+            BoxEnum::ScrollingFrame(_) => ScrollingFrame::NAME,
+            BoxEnum::Slider(_) => Slider::NAME,
+            BoxEnum::TabFrame(_) => TabFrame::NAME,
+        },
+        View::Image(_) => Image::NAME,
+        View::Label(_) => Label::NAME,
+        View::ProgressSpinner(_) => ProgressSpinner::NAME,
+        View::Rectangle(_) => Rectangle::NAME,
+    }
+}
+
+/// Why a BoundView could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindError {
+    /// bind() has not been called, or the root view has been freed
+    NotBound { id: String },
+    /// No view with that id in the root view
+    NotFound { id: String },
+    /// The view exists but is not of the requested type
+    TypeMismatch {
+        id: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindError::NotBound { id } => write!(f, "view \"{}\" is not bound to any view tree", id),
+            BindError::NotFound { id } => write!(f, "no view with id \"{}\"", id),
+            BindError::TypeMismatch { id, expected, found } => write!(
+                f,
+                "view \"{}\" has type {}, expected {}",
+                id, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for BindError {}
+
+/// A reference to a view of a view tree, looked up by id the first
+/// time it is accessed and downcast to the requested view type.
+///
+/// Usually declared in activities with `#[bind("id")]` and `#[derive(BindViews)]`,
+/// the activity binds them to its content view once it is inflated.
+pub struct BoundView<T: ViewCast> {
     id: String,
-    v: Option<T>
+    root: Weak<RefCell<View>>,
+    view: OnceCell<Rc<RefCell<View>>>,
+    _type: PhantomData<T>,
 }
 
-impl <T> BoundView<T>
-where T: ViewTrait
+impl<T> BoundView<T>
+where
+    T: ViewCast,
 {
     pub fn new(id: &str) -> BoundView<T> {
         Self {
             id: id.into(),
-            v: None
+            root: Weak::new(),
+            view: OnceCell::new(),
+            _type: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn set_id(&mut self, id: &str) {
+        self.id = id.into();
+        self.view = OnceCell::new();
+    }
+
+    /**
+     * Sets the view tree to look the view in. The lookup itself
+     * is only done on first access.
+     */
+    pub fn bind(&mut self, root: &Rc<RefCell<View>>) {
+        self.root = Rc::downgrade(root);
+        self.view = OnceCell::new();
+    }
+
+    /**
+     * Returns the view, looking it up if it has not been done yet.
+     */
+    pub fn view(&self) -> Result<Rc<RefCell<View>>, BindError> {
+        self.resolve().cloned()
+    }
+
+    /**
+     * Borrows the view as its concrete type.
+     */
+    pub fn get(&self) -> Result<Ref<'_, T>, BindError> {
+        let view = self.resolve()?.borrow();
+        Ref::filter_map(view, T::cast).map_err(|view| self.type_mismatch(&view))
+    }
+
+    /**
+     * Mutably borrows the view as its concrete type.
+     */
+    pub fn get_mut(&self) -> Result<RefMut<'_, T>, BindError> {
+        let view = self.resolve()?.borrow_mut();
+        RefMut::filter_map(view, T::cast_mut).map_err(|view| self.type_mismatch(&view))
+    }
+
+    fn resolve(&self) -> Result<&Rc<RefCell<View>>, BindError> {
+        if let Some(view) = self.view.get() {
+            return Ok(view);
+        }
+
+        let root = self.root.upgrade().ok_or_else(|| BindError::NotBound {
+            id: self.id.clone(),
+        })?;
+        let view = root
+            .borrow()
+            .get_view(&self.id)
+            .ok_or_else(|| BindError::NotFound { id: self.id.clone() })?;
+
+        if T::cast(&view.borrow()).is_none() {
+            return Err(self.type_mismatch(&view.borrow()));
         }
+
+        Ok(self.view.get_or_init(|| view))
     }
-}
\ No newline at end of file
+
+    fn type_mismatch(&self, view: &View) -> BindError {
+        BindError::TypeMismatch {
+            id: self.id.clone(),
+            expected: T::NAME,
+            found: view_type_name(view),
+        }
+    }
+}
+
+impl<T: ViewCast> Default for BoundView<T> {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+/// Binds every BoundView of a struct to a view tree,
+/// see the BindViews derive macro.
+pub trait BindViews {
+    fn bind_views(&mut self, root: &Rc<RefCell<View>>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BindError, BoundView};
+    use crate::core::view_base::View;
+    use crate::core::view_box::BoxTrait;
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use crate::views::image::Image;
+    use crate::views::label::{Label, LabelTrait};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn parse(xml: &str) -> Rc<RefCell<View>> {
+        XMLParser::new(&XMLViewRegistry::new(), "<test>".into(), xml)
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn view_is_looked_up_on_first_access() {
+        let root = parse("<brls:Box/>");
+        let mut title = BoundView::<Label>::new("title");
+        title.bind(&root);
+
+        // Added after bind(), still found
+        let label = parse(r#"<brls:Label id="title" text="Hello"/>"#);
+        if let View::Box(root) = &mut *root.borrow_mut() {
+            root.add_view(label.clone());
+        }
+        assert_eq!(title.get().unwrap().full_text(), "Hello");
+        assert!(Rc::ptr_eq(&title.view().unwrap(), &label));
+    }
+
+    #[test]
+    fn missing_view_is_not_found() {
+        let root = parse("<brls:Box/>");
+        let mut title = BoundView::<Label>::new("title");
+        title.bind(&root);
+
+        assert_eq!(
+            title.view().err(),
+            Some(BindError::NotFound { id: "title".into() })
+        );
+    }
+
+    #[test]
+    fn view_of_another_type_is_a_mismatch() {
+        let root = parse(r#"<brls:Box><brls:Label id="title"/></brls:Box>"#);
+        let mut title = BoundView::<Image>::new("title");
+        title.bind(&root);
+
+        let error = BindError::TypeMismatch {
+            id: "title".into(),
+            expected: "Image",
+            found: "Label",
+        };
+        assert_eq!(title.get().err(), Some(error.clone()));
+        assert_eq!(title.get_mut().err(), Some(error));
+    }
+
+    #[test]
+    fn unbound_or_freed_root_is_not_bound() {
+        let title = BoundView::<Label>::new("title");
+        let error = Some(BindError::NotBound { id: "title".into() });
+        assert_eq!(title.view().err(), error);
+
+        let mut title = BoundView::<Label>::new("title");
+        title.bind(&parse(r#"<brls:Box><brls:Label id="title"/></brls:Box>"#));
+        assert_eq!(title.view().err(), error);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -8,6 +8,13 @@ impl Point {
     pub fn new(x: f32, y: f32) -> Self {
         Self { x, y }
     }
+
+    /**
+     * Returns the distance between the two points.
+     */
+    pub fn distance(&self, other: &Point) -> f32 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
 }
 
 impl Default for Point {
@@ -76,6 +83,16 @@ impl Rect {
     pub fn height(&self) -> f32 {
         self.size.height
     }
+
+    /**
+     * Returns true if the point is inside the rect, edges included.
+     */
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.min_x()
+            && point.x <= self.max_x()
+            && point.y >= self.min_y()
+            && point.y <= self.max_y()
+    }
 }
 
 impl Default for Rect {
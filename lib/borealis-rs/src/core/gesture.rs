@@ -0,0 +1,774 @@
+use crate::core::geometry::Point;
+use crate::core::time::Time;
+use crate::core::view_base::{View, ViewBase};
+use crate::core::view_drawer::ViewDrawer;
+use crate::core::view_layout::ViewLayout;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Distance in pixels a finger can move before a tap or a long press fails,
+/// and before a pan starts.
+pub const TOUCH_SLOP: f32 = 24.0;
+/// Time in ms a finger has to stay still to make a long press.
+pub const LONG_PRESS_DURATION: Time = 500;
+/// Minimum distance in pixels, and speed in pixels per second, of a swipe.
+pub const SWIPE_MIN_DISTANCE: f32 = 60.0;
+pub const SWIPE_MIN_VELOCITY: f32 = 600.0;
+/// Time in ms of the most recent positions used to compute a velocity.
+const VELOCITY_WINDOW: Time = 100;
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum TouchPhase {
+    Start,
+    Stay,
+    End,
+}
+
+/// A finger on the screen, or the mouse while its left button is pressed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TouchState {
+    pub finger_id: u64,
+    pub phase: TouchPhase,
+    pub position: Point,
+    /// Time of the event in microseconds, see Clock.
+    pub time: Time,
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum GestureState {
+    /// Another gesture has been recognized instead
+    Interrupted,
+    /// The touch may still become this gesture
+    Unsure,
+    /// The gesture has just been recognized
+    Start,
+    /// The gesture goes on
+    Stay,
+    /// The gesture is over
+    End,
+    /// The touch is not this gesture
+    Failed,
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum PanAxis {
+    Horizontal,
+    Vertical,
+    Any,
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+pub type SharedGestureRecognizer = Rc<RefCell<Box<dyn GestureRecognizer>>>;
+
+/// Turns the touches going through a view into a gesture.
+///
+/// Touches are given to the recognizers of the view under the finger
+/// (the first responder) then to the ones of its parents. When a recognizer
+/// returns GestureState::Start, the recognizers of that chain still unsure
+/// are interrupted, so that only one gesture wins.
+pub trait GestureRecognizer {
+    /**
+     * Called for each touch event of a finger that started on the view
+     * or one of its children. Returns the new state of the recognizer.
+     */
+    fn recognition_loop(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) -> GestureState;
+
+    fn state(&self) -> GestureState;
+
+    fn set_state(&mut self, state: GestureState);
+
+    /**
+     * Stops the recognition until the next touch starts.
+     * Called when another gesture has been recognized.
+     */
+    fn interrupt(&mut self, only_if_unsure: bool) {
+        if !only_if_unsure || self.state() == GestureState::Unsure {
+            self.set_state(GestureState::Interrupted);
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+/**
+ * Gives the touch to the recognizers of the first responder, then to the ones
+ * of its parents. Interrupts the unsure recognizers of the chain as soon
+ * as one of them recognizes its gesture.
+ */
+pub fn dispatch_touch(first_responder: &Rc<RefCell<View>>, touch: &TouchState) {
+    let mut current = Some(first_responder.clone());
+    while let Some(view) = current {
+        // Recognizers are called with the view not borrowed, their callbacks can change it
+        let recognizers = view.borrow().data().gesture_recognizers.clone();
+        for recognizer in recognizers {
+            let state = {
+                let mut recognizer = recognizer.borrow_mut();
+                if !recognizer.is_enabled() {
+                    continue;
+                }
+                recognizer.recognition_loop(touch, &view)
+            };
+
+            if state == GestureState::Start {
+                interrupt_gestures(first_responder, true);
+            }
+        }
+
+        current = view.borrow().parent();
+    }
+}
+
+/**
+ * Interrupts the recognizers of the view and its parents.
+ */
+pub fn interrupt_gestures(view: &Rc<RefCell<View>>, only_if_unsure: bool) {
+    let mut current = Some(view.clone());
+    while let Some(view) = current {
+        let recognizers = view.borrow().data().gesture_recognizers.clone();
+        for recognizer in recognizers {
+            // Recognizers interrupting from their callback are still borrowed
+            if let Ok(mut recognizer) = recognizer.try_borrow_mut() {
+                recognizer.interrupt(only_if_unsure);
+            }
+        }
+
+        current = view.borrow().parent();
+    }
+}
+
+/// Computes the velocity of a finger from its most recent positions.
+#[derive(Debug, Default, Clone)]
+pub struct VelocityTracker {
+    samples: VecDeque<(Point, Time)>,
+}
+
+impl VelocityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn add(&mut self, position: Point, time: Time) {
+        self.samples.push_back((position, time));
+
+        // Keep one sample older than the window to always have a time span
+        while self.samples.len() > 2
+            && time.saturating_sub(self.samples[1].1) > VELOCITY_WINDOW * 1000
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /**
+     * Returns the velocity in pixels per second.
+     */
+    pub fn velocity(&self) -> Point {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((first, first_time)), Some((last, last_time))) if last_time > first_time => {
+                let seconds = (last_time - first_time) as f32 / 1000000.0;
+                Point::new((last.x - first.x) / seconds, (last.y - first.y) / seconds)
+            }
+            _ => Point::default(),
+        }
+    }
+}
+
+/// Recognizes a finger going down and up on the view without moving,
+/// and plays the click pulse of the view.
+pub struct TapGestureRecognizer {
+    state: GestureState,
+    start_position: Point,
+    callback: Box<dyn Fn(Rc<RefCell<View>>)>,
+}
+
+impl TapGestureRecognizer {
+    pub fn new(callback: Box<dyn Fn(Rc<RefCell<View>>)>) -> Self {
+        Self {
+            state: GestureState::Failed,
+            start_position: Point::default(),
+            callback,
+        }
+    }
+}
+
+impl GestureRecognizer for TapGestureRecognizer {
+    fn recognition_loop(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) -> GestureState {
+        match touch.phase {
+            TouchPhase::Start => {
+                self.state = GestureState::Unsure;
+                self.start_position = touch.position;
+            }
+            TouchPhase::Stay => {
+                if self.state == GestureState::Unsure
+                    && touch.position.distance(&self.start_position) > TOUCH_SLOP
+                {
+                    self.state = GestureState::Failed;
+                }
+            }
+            TouchPhase::End => {
+                if self.state == GestureState::Unsure
+                    && view.borrow().rect().contains(&touch.position)
+                {
+                    self.state = GestureState::End;
+                    view.borrow_mut().play_click_animation(false);
+                    (self.callback)(view.clone());
+                } else if self.state == GestureState::Unsure {
+                    self.state = GestureState::Failed;
+                }
+            }
+        }
+
+        self.state
+    }
+
+    fn state(&self) -> GestureState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: GestureState) {
+        self.state = state;
+    }
+}
+
+/// Recognizes a finger staying still on the view for LONG_PRESS_DURATION.
+/// The callback is called as soon as the duration is reached.
+pub struct LongPressGestureRecognizer {
+    state: GestureState,
+    start_position: Point,
+    start_time: Time,
+    duration: Time,
+    callback: Box<dyn Fn(Rc<RefCell<View>>)>,
+}
+
+impl LongPressGestureRecognizer {
+    pub fn new(callback: Box<dyn Fn(Rc<RefCell<View>>)>) -> Self {
+        Self {
+            state: GestureState::Failed,
+            start_position: Point::default(),
+            start_time: 0,
+            duration: LONG_PRESS_DURATION,
+            callback,
+        }
+    }
+
+    /**
+     * Sets the time in ms the finger has to stay on the view.
+     */
+    pub fn set_duration(&mut self, duration: Time) {
+        self.duration = duration;
+    }
+}
+
+impl GestureRecognizer for LongPressGestureRecognizer {
+    fn recognition_loop(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) -> GestureState {
+        match touch.phase {
+            TouchPhase::Start => {
+                self.state = GestureState::Unsure;
+                self.start_position = touch.position;
+                self.start_time = touch.time;
+            }
+            TouchPhase::Stay => {
+                if self.state == GestureState::Unsure {
+                    if touch.position.distance(&self.start_position) > TOUCH_SLOP {
+                        self.state = GestureState::Failed;
+                    } else if touch.time.saturating_sub(self.start_time) >= self.duration * 1000 {
+                        self.state = GestureState::Start;
+                        (self.callback)(view.clone());
+                        return self.state;
+                    }
+                }
+
+                // Wait for the finger to go up once recognized
+                if self.state == GestureState::Start {
+                    self.state = GestureState::Stay;
+                }
+            }
+            TouchPhase::End => {
+                self.state = match self.state {
+                    GestureState::Start | GestureState::Stay => GestureState::End,
+                    GestureState::Unsure => GestureState::Failed,
+                    state => state,
+                };
+            }
+        }
+
+        self.state
+    }
+
+    fn state(&self) -> GestureState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: GestureState) {
+        self.state = state;
+    }
+}
+
+/// What a pan gesture is doing, given to the pan callback.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PanGestureStatus {
+    pub state: GestureState,
+    pub position: Point,
+    pub start_position: Point,
+    /// Movement since the previous call
+    pub delta: Point,
+    /// In pixels per second
+    pub velocity: Point,
+}
+
+/// Recognizes a finger moving along the given axis. The callback is called
+/// when the pan starts, for each movement, and when the finger goes up.
+pub struct PanGestureRecognizer {
+    state: GestureState,
+    axis: PanAxis,
+    start_position: Point,
+    last_position: Point,
+    velocity: VelocityTracker,
+    callback: Box<dyn Fn(Rc<RefCell<View>>, &PanGestureStatus)>,
+}
+
+impl PanGestureRecognizer {
+    pub fn new(axis: PanAxis, callback: Box<dyn Fn(Rc<RefCell<View>>, &PanGestureStatus)>) -> Self {
+        Self {
+            state: GestureState::Failed,
+            axis,
+            start_position: Point::default(),
+            last_position: Point::default(),
+            velocity: VelocityTracker::new(),
+            callback,
+        }
+    }
+
+    pub fn axis(&self) -> PanAxis {
+        self.axis
+    }
+
+    fn has_moved_enough(&self, position: &Point) -> bool {
+        let dx = (position.x - self.start_position.x).abs();
+        let dy = (position.y - self.start_position.y).abs();
+        match self.axis {
+            PanAxis::Horizontal => dx > TOUCH_SLOP && dx > dy,
+            PanAxis::Vertical => dy > TOUCH_SLOP && dy > dx,
+            PanAxis::Any => position.distance(&self.start_position) > TOUCH_SLOP,
+        }
+    }
+
+    fn respond(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) {
+        let status = PanGestureStatus {
+            state: self.state,
+            position: touch.position,
+            start_position: self.start_position,
+            delta: Point::new(
+                touch.position.x - self.last_position.x,
+                touch.position.y - self.last_position.y,
+            ),
+            velocity: self.velocity.velocity(),
+        };
+        self.last_position = touch.position;
+        (self.callback)(view.clone(), &status);
+    }
+}
+
+impl GestureRecognizer for PanGestureRecognizer {
+    fn recognition_loop(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) -> GestureState {
+        match touch.phase {
+            TouchPhase::Start => {
+                self.state = GestureState::Unsure;
+                self.start_position = touch.position;
+                self.last_position = touch.position;
+                self.velocity.clear();
+                self.velocity.add(touch.position, touch.time);
+            }
+            TouchPhase::Stay => {
+                self.velocity.add(touch.position, touch.time);
+                match self.state {
+                    GestureState::Unsure if self.has_moved_enough(&touch.position) => {
+                        self.state = GestureState::Start;
+                        self.respond(touch, view);
+                    }
+                    GestureState::Start | GestureState::Stay => {
+                        self.state = GestureState::Stay;
+                        if touch.position != self.last_position {
+                            self.respond(touch, view);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::End => {
+                self.velocity.add(touch.position, touch.time);
+                match self.state {
+                    GestureState::Start | GestureState::Stay => {
+                        self.state = GestureState::End;
+                        self.respond(touch, view);
+                    }
+                    GestureState::Unsure => self.state = GestureState::Failed,
+                    _ => {}
+                }
+            }
+        }
+
+        self.state
+    }
+
+    fn state(&self) -> GestureState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: GestureState) {
+        self.state = state;
+    }
+}
+
+/// Recognizes a quick movement in one direction, when the finger goes up
+/// after moving at least SWIPE_MIN_DISTANCE at SWIPE_MIN_VELOCITY.
+pub struct SwipeGestureRecognizer {
+    state: GestureState,
+    start_position: Point,
+    velocity: VelocityTracker,
+    callback: Box<dyn Fn(Rc<RefCell<View>>, SwipeDirection)>,
+}
+
+impl SwipeGestureRecognizer {
+    pub fn new(callback: Box<dyn Fn(Rc<RefCell<View>>, SwipeDirection)>) -> Self {
+        Self {
+            state: GestureState::Failed,
+            start_position: Point::default(),
+            velocity: VelocityTracker::new(),
+            callback,
+        }
+    }
+
+    fn direction(&self, position: &Point) -> Option<SwipeDirection> {
+        let dx = position.x - self.start_position.x;
+        let dy = position.y - self.start_position.y;
+        let velocity = self.velocity.velocity();
+
+        if dx.abs() >= dy.abs() {
+            if dx.abs() < SWIPE_MIN_DISTANCE || velocity.x.abs() < SWIPE_MIN_VELOCITY {
+                return None;
+            }
+            Some(match dx > 0.0 {
+                true => SwipeDirection::Right,
+                false => SwipeDirection::Left,
+            })
+        } else {
+            if dy.abs() < SWIPE_MIN_DISTANCE || velocity.y.abs() < SWIPE_MIN_VELOCITY {
+                return None;
+            }
+            Some(match dy > 0.0 {
+                true => SwipeDirection::Down,
+                false => SwipeDirection::Up,
+            })
+        }
+    }
+}
+
+impl GestureRecognizer for SwipeGestureRecognizer {
+    fn recognition_loop(&mut self, touch: &TouchState, view: &Rc<RefCell<View>>) -> GestureState {
+        match touch.phase {
+            TouchPhase::Start => {
+                self.state = GestureState::Unsure;
+                self.start_position = touch.position;
+                self.velocity.clear();
+                self.velocity.add(touch.position, touch.time);
+            }
+            TouchPhase::Stay => {
+                self.velocity.add(touch.position, touch.time);
+            }
+            TouchPhase::End => {
+                self.velocity.add(touch.position, touch.time);
+                if self.state == GestureState::Unsure {
+                    match self.direction(&touch.position) {
+                        Some(direction) => {
+                            self.state = GestureState::End;
+                            (self.callback)(view.clone(), direction);
+                        }
+                        None => self.state = GestureState::Failed,
+                    }
+                }
+            }
+        }
+
+        self.state
+    }
+
+    fn state(&self) -> GestureState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: GestureState) {
+        self.state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use std::cell::Cell;
+
+    fn view() -> Rc<RefCell<View>> {
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box width="200" height="100"/>"#;
+        let view = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        view.borrow().invalidate();
+        view
+    }
+
+    fn touch(phase: TouchPhase, x: f32, y: f32, time_ms: Time) -> TouchState {
+        TouchState {
+            finger_id: 0,
+            phase,
+            position: Point::new(x, y),
+            time: time_ms * 1000,
+        }
+    }
+
+    fn counter() -> (Rc<Cell<u32>>, Box<dyn Fn(Rc<RefCell<View>>)>) {
+        let count = Rc::new(Cell::new(0));
+        let callback_count = count.clone();
+        let callback = Box::new(move |_| callback_count.set(callback_count.get() + 1));
+        (count, callback)
+    }
+
+    #[test]
+    fn velocity_tracker_uses_the_recent_positions() {
+        let mut tracker = VelocityTracker::new();
+        assert_eq!(tracker.velocity(), Point::default());
+
+        tracker.add(Point::new(0.0, 0.0), 0);
+        tracker.add(Point::new(100.0, 50.0), 100_000);
+        assert_eq!(tracker.velocity(), Point::new(1000.0, 500.0));
+
+        // The first positions are dropped once older than the window
+        tracker.add(Point::new(100.0, 50.0), 1_000_000);
+        tracker.add(Point::new(100.0, 50.0), 1_050_000);
+        assert_eq!(tracker.velocity(), Point::default());
+    }
+
+    #[test]
+    fn tap_within_the_slop() {
+        let view = view();
+        let center = {
+            let rect = view.borrow().rect();
+            Point::new(rect.mid_x(), rect.mid_y())
+        };
+        let (count, callback) = counter();
+        let mut tap = TapGestureRecognizer::new(callback);
+
+        let (x, y) = (center.x, center.y);
+        let start = touch(TouchPhase::Start, x, y, 0);
+        assert_eq!(tap.recognition_loop(&start, &view), GestureState::Unsure);
+        let stay = touch(TouchPhase::Stay, x + TOUCH_SLOP, y, 50);
+        assert_eq!(tap.recognition_loop(&stay, &view), GestureState::Unsure);
+        let end = touch(TouchPhase::End, x, y, 100);
+        assert_eq!(tap.recognition_loop(&end, &view), GestureState::End);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn tap_fails_beyond_the_slop_or_outside_the_view() {
+        let view = view();
+        let rect = view.borrow().rect();
+        let (x, y) = (rect.mid_x(), rect.mid_y());
+        let (count, callback) = counter();
+        let mut tap = TapGestureRecognizer::new(callback);
+
+        tap.recognition_loop(&touch(TouchPhase::Start, x, y, 0), &view);
+        let stay = touch(TouchPhase::Stay, x + TOUCH_SLOP + 1.0, y, 50);
+        assert_eq!(tap.recognition_loop(&stay, &view), GestureState::Failed);
+        let end = touch(TouchPhase::End, x, y, 100);
+        assert_eq!(tap.recognition_loop(&end, &view), GestureState::Failed);
+
+        tap.recognition_loop(&touch(TouchPhase::Start, x, y, 200), &view);
+        let end = touch(TouchPhase::End, rect.max_x() + 1.0, y, 300);
+        assert_eq!(tap.recognition_loop(&end, &view), GestureState::Failed);
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn long_press_at_its_duration() {
+        let view = view();
+        let (count, callback) = counter();
+        let mut long_press = LongPressGestureRecognizer::new(callback);
+
+        long_press.recognition_loop(&touch(TouchPhase::Start, 10.0, 10.0, 0), &view);
+        let before = touch(TouchPhase::Stay, 12.0, 10.0, LONG_PRESS_DURATION - 1);
+        assert_eq!(
+            long_press.recognition_loop(&before, &view),
+            GestureState::Unsure
+        );
+        assert_eq!(count.get(), 0);
+
+        let at = touch(TouchPhase::Stay, 12.0, 10.0, LONG_PRESS_DURATION);
+        assert_eq!(long_press.recognition_loop(&at, &view), GestureState::Start);
+        assert_eq!(count.get(), 1);
+
+        let after = touch(TouchPhase::Stay, 12.0, 10.0, LONG_PRESS_DURATION + 100);
+        assert_eq!(
+            long_press.recognition_loop(&after, &view),
+            GestureState::Stay
+        );
+        let end = touch(TouchPhase::End, 12.0, 10.0, LONG_PRESS_DURATION + 200);
+        assert_eq!(long_press.recognition_loop(&end, &view), GestureState::End);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn long_press_fails_when_the_finger_moves() {
+        let view = view();
+        let (count, callback) = counter();
+        let mut long_press = LongPressGestureRecognizer::new(callback);
+
+        long_press.recognition_loop(&touch(TouchPhase::Start, 10.0, 10.0, 0), &view);
+        let moved = touch(TouchPhase::Stay, 10.0, 50.0, 100);
+        assert_eq!(
+            long_press.recognition_loop(&moved, &view),
+            GestureState::Failed
+        );
+        let late = touch(TouchPhase::Stay, 10.0, 10.0, LONG_PRESS_DURATION);
+        assert_eq!(
+            long_press.recognition_loop(&late, &view),
+            GestureState::Failed
+        );
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn pan_locks_on_its_axis() {
+        let view = view();
+        let statuses = Rc::new(RefCell::new(Vec::new()));
+        let callback_statuses = statuses.clone();
+        let mut pan = PanGestureRecognizer::new(
+            PanAxis::Horizontal,
+            Box::new(move |_, status| callback_statuses.borrow_mut().push(*status)),
+        );
+
+        // A vertical move never starts a horizontal pan
+        pan.recognition_loop(&touch(TouchPhase::Start, 0.0, 0.0, 0), &view);
+        let vertical = touch(TouchPhase::Stay, 10.0, 100.0, 50);
+        assert_eq!(pan.recognition_loop(&vertical, &view), GestureState::Unsure);
+        let end = touch(TouchPhase::End, 10.0, 100.0, 100);
+        assert_eq!(pan.recognition_loop(&end, &view), GestureState::Failed);
+        assert!(statuses.borrow().is_empty());
+
+        pan.recognition_loop(&touch(TouchPhase::Start, 0.0, 0.0, 1000), &view);
+        let small = touch(TouchPhase::Stay, TOUCH_SLOP, 0.0, 1010);
+        assert_eq!(pan.recognition_loop(&small, &view), GestureState::Unsure);
+        let start = touch(TouchPhase::Stay, 50.0, 10.0, 1050);
+        assert_eq!(pan.recognition_loop(&start, &view), GestureState::Start);
+        let stay = touch(TouchPhase::Stay, 100.0, 10.0, 1100);
+        assert_eq!(pan.recognition_loop(&stay, &view), GestureState::Stay);
+        let end = touch(TouchPhase::End, 100.0, 10.0, 1100);
+        assert_eq!(pan.recognition_loop(&end, &view), GestureState::End);
+
+        let statuses = statuses.borrow();
+        let states: Vec<GestureState> = statuses.iter().map(|status| status.state).collect();
+        assert_eq!(
+            states,
+            vec![GestureState::Start, GestureState::Stay, GestureState::End]
+        );
+        assert_eq!(statuses[0].start_position, Point::new(0.0, 0.0));
+        assert_eq!(statuses[0].delta, Point::new(50.0, 10.0));
+        assert_eq!(statuses[1].delta, Point::new(50.0, 0.0));
+        // 100 px in 100 ms
+        assert_eq!(statuses[1].velocity, Point::new(1000.0, 100.0));
+    }
+
+    fn swipe(to: Point, duration_ms: Time) -> (GestureState, Option<SwipeDirection>) {
+        let view = view();
+        let direction = Rc::new(Cell::new(None));
+        let callback_direction = direction.clone();
+        let mut swipe = SwipeGestureRecognizer::new(Box::new(move |_, direction| {
+            callback_direction.set(Some(direction))
+        }));
+
+        swipe.recognition_loop(&touch(TouchPhase::Start, 100.0, 100.0, 0), &view);
+        let end = touch(TouchPhase::End, to.x, to.y, duration_ms);
+        (swipe.recognition_loop(&end, &view), direction.get())
+    }
+
+    #[test]
+    fn swipe_below_the_thresholds_fails() {
+        // Fast enough but too short
+        let short = Point::new(100.0 + SWIPE_MIN_DISTANCE - 1.0, 100.0);
+        assert_eq!(swipe(short, 10), (GestureState::Failed, None));
+        // Far enough but too slow: 100 px in 200 ms
+        assert_eq!(
+            swipe(Point::new(200.0, 100.0), 200),
+            (GestureState::Failed, None)
+        );
+    }
+
+    #[test]
+    fn swipe_above_the_thresholds_gives_its_direction() {
+        // 100 px in 50 ms
+        assert_eq!(
+            swipe(Point::new(200.0, 100.0), 50),
+            (GestureState::End, Some(SwipeDirection::Right))
+        );
+        assert_eq!(
+            swipe(Point::new(0.0, 90.0), 50),
+            (GestureState::End, Some(SwipeDirection::Left))
+        );
+        assert_eq!(
+            swipe(Point::new(110.0, 0.0), 50),
+            (GestureState::End, Some(SwipeDirection::Up))
+        );
+        assert_eq!(
+            swipe(Point::new(90.0, 200.0), 50),
+            (GestureState::End, Some(SwipeDirection::Down))
+        );
+    }
+
+    #[test]
+    fn starting_gesture_interrupts_the_unsure_ones() {
+        let view = view();
+        let (taps, tap_callback) = counter();
+        let (long_presses, long_press_callback) = counter();
+        view.borrow_mut()
+            .add_gesture_recognizer(Box::new(TapGestureRecognizer::new(tap_callback)));
+        view.borrow_mut()
+            .add_gesture_recognizer(Box::new(LongPressGestureRecognizer::new(
+                long_press_callback,
+            )));
+        let states = || -> Vec<GestureState> {
+            view.borrow()
+                .gesture_recognizers()
+                .iter()
+                .map(|recognizer| recognizer.borrow().state())
+                .collect()
+        };
+
+        dispatch_touch(&view, &touch(TouchPhase::Start, 0.0, 0.0, 0));
+        assert_eq!(states(), vec![GestureState::Unsure, GestureState::Unsure]);
+
+        let at = touch(TouchPhase::Stay, 0.0, 0.0, LONG_PRESS_DURATION);
+        dispatch_touch(&view, &at);
+        assert_eq!(
+            states(),
+            vec![GestureState::Interrupted, GestureState::Start]
+        );
+
+        dispatch_touch(&view, &touch(TouchPhase::End, 0.0, 0.0, 600));
+        assert_eq!(states(), vec![GestureState::Interrupted, GestureState::End]);
+        assert_eq!(taps.get(), 0);
+        assert_eq!(long_presses.get(), 1);
+    }
+}
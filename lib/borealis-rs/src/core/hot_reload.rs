@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Delay between two checks of the watched files.
+pub const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches XML files for changes during development, by polling their
+/// modification time so that no platform specific watcher is needed.
+pub struct XMLWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    next_check: Instant,
+}
+
+impl XMLWatcher {
+    pub fn new() -> Self {
+        Self {
+            files: HashMap::new(),
+            next_check: Instant::now() + HOT_RELOAD_INTERVAL,
+        }
+    }
+
+    /**
+     * Starts watching the given file. Its current content is the reference,
+     * it will only be reported once modified.
+     */
+    pub fn watch(&mut self, path: PathBuf) {
+        let modified = modified_time(&path);
+        self.files.entry(path).or_insert(modified);
+    }
+
+    /**
+     * Returns the files modified since the last call, or nothing
+     * if HOT_RELOAD_INTERVAL has not elapsed since then.
+     */
+    pub fn changed_files(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        if now < self.next_check {
+            return vec![];
+        }
+        self.next_check = now + HOT_RELOAD_INTERVAL;
+
+        let mut changed = vec![];
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /**
+     * Returns when the event loop should wake up for the next check.
+     */
+    pub fn next_check(&self) -> Instant {
+        self.next_check
+    }
+}
+
+impl Default for XMLWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,88 @@
+use crate::core::resource::resources_path;
+use anyhow::{bail, Context};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref GLOBAL_I18N: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/**
+ * Returns the translation of the key, or the key itself if it has none,
+ * so that a missing translation shows up on screen instead of panicking.
+ */
+pub fn translation(key: &str) -> String {
+    try_translation(key).unwrap_or_else(|| {
+        warn!("unknown translation: {}", key);
+        key.into()
+    })
+}
+
+pub fn add_translation(key: &str, value: &str) {
+    let mut map = GLOBAL_I18N.lock().unwrap();
+    map.insert(key.into(), value.into());
+}
+
+pub fn try_translation(key: &str) -> Option<String> {
+    let map = GLOBAL_I18N.lock().unwrap();
+    map.get(key).cloned()
+}
+
+/**
+ * Loads every "i18n/<locale>/<name>.json" file of the resources directory.
+ *
+ * Keys are prefixed by the file name and nested objects are joined
+ * with '/': "title" in "demo.json" becomes "@i18n/demo/title".
+ */
+pub fn load_translations(locale: &str) -> anyhow::Result<()> {
+    let directory = resources_path().join("i18n").join(locale);
+    let entries = fs::read_dir(&directory)
+        .with_context(|| format!("unable to read translations directory {:?}", directory))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().map_or(true, |extension| extension != "json") {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("unable to read translation file {:?}", path))?;
+        let value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("invalid translation file {:?}", path))?;
+
+        let prefix = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        add_translations(&prefix, &value)
+            .with_context(|| format!("invalid translation file {:?}", path))?;
+    }
+
+    Ok(())
+}
+
+fn add_translations(key: &str, value: &Value) -> anyhow::Result<()> {
+    match value {
+        Value::String(text) => add_translation(key, text),
+        Value::Object(children) => {
+            for (name, child) in children {
+                add_translations(&format!("{}/{}", key, name), child)?;
+            }
+        }
+        _ => bail!("\"{}\" must be a string or an object", key),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_translation_falls_back_to_its_key() {
+        add_translation("i18n_test/title", "Title");
+        assert_eq!(translation("i18n_test/title"), "Title");
+        assert_eq!(try_translation("i18n_test/missing"), None);
+        assert_eq!(translation("i18n_test/missing"), "i18n_test/missing");
+    }
+}
@@ -0,0 +1,665 @@
+use anyhow::{bail, Context};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use winit::event::VirtualKeyCode;
+
+use crate::core::time::Time;
+use crate::core::view_base::FocusDirection;
+
+/// File of the resources directory the keyboard mapping is loaded from, if it exists.
+pub const INPUT_CONFIG_FILE: &str = "config/input.json";
+
+/// Position from which the left stick counts as the D-pad.
+pub const STICK_DPAD_THRESHOLD: f32 = 0.5;
+/// Time in ms a D-pad button has to be held before it repeats,
+/// then time in ms between two repeats.
+pub const BUTTON_REPEAT_DELAY: Time = 500;
+pub const BUTTON_REPEAT_INTERVAL: Time = 100;
+
+/// Abstract controller buttons the application reacts to,
+/// whatever the device they come from.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum ControllerButton {
+    A,
+    B,
+    X,
+    Y,
+    Up,
+    Down,
+    Left,
+    Right,
+    L,
+    R,
+    Start,
+    Select,
+}
+
+impl ControllerButton {
+    pub const ALL: [ControllerButton; 12] = [
+        ControllerButton::A,
+        ControllerButton::B,
+        ControllerButton::X,
+        ControllerButton::Y,
+        ControllerButton::Up,
+        ControllerButton::Down,
+        ControllerButton::Left,
+        ControllerButton::Right,
+        ControllerButton::L,
+        ControllerButton::R,
+        ControllerButton::Start,
+        ControllerButton::Select,
+    ];
+
+    /**
+     * Returns the name of the button, as written in the input config file.
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControllerButton::A => "a",
+            ControllerButton::B => "b",
+            ControllerButton::X => "x",
+            ControllerButton::Y => "y",
+            ControllerButton::Up => "up",
+            ControllerButton::Down => "down",
+            ControllerButton::Left => "left",
+            ControllerButton::Right => "right",
+            ControllerButton::L => "l",
+            ControllerButton::R => "r",
+            ControllerButton::Start => "start",
+            ControllerButton::Select => "select",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ControllerButton> {
+        ControllerButton::ALL
+            .into_iter()
+            .find(|button| button.name() == name)
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /**
+     * Returns the focus direction of the D-pad buttons.
+     */
+    pub fn focus_direction(&self) -> Option<FocusDirection> {
+        match self {
+            ControllerButton::Up => Some(FocusDirection::Up),
+            ControllerButton::Down => Some(FocusDirection::Down),
+            ControllerButton::Left => Some(FocusDirection::Left),
+            ControllerButton::Right => Some(FocusDirection::Right),
+            _ => None,
+        }
+    }
+}
+
+/// The Debug names of winit's VirtualKeyCode, the key names accepted by KeyboardMapping.
+const KEY_NAMES: &[&str] = &[
+    "Key1",
+    "Key2",
+    "Key3",
+    "Key4",
+    "Key5",
+    "Key6",
+    "Key7",
+    "Key8",
+    "Key9",
+    "Key0",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "Escape",
+    "F1",
+    "F2",
+    "F3",
+    "F4",
+    "F5",
+    "F6",
+    "F7",
+    "F8",
+    "F9",
+    "F10",
+    "F11",
+    "F12",
+    "F13",
+    "F14",
+    "F15",
+    "F16",
+    "F17",
+    "F18",
+    "F19",
+    "F20",
+    "F21",
+    "F22",
+    "F23",
+    "F24",
+    "Snapshot",
+    "Scroll",
+    "Pause",
+    "Insert",
+    "Home",
+    "Delete",
+    "End",
+    "PageDown",
+    "PageUp",
+    "Left",
+    "Up",
+    "Right",
+    "Down",
+    "Back",
+    "Return",
+    "Space",
+    "Compose",
+    "Caret",
+    "Numlock",
+    "Numpad0",
+    "Numpad1",
+    "Numpad2",
+    "Numpad3",
+    "Numpad4",
+    "Numpad5",
+    "Numpad6",
+    "Numpad7",
+    "Numpad8",
+    "Numpad9",
+    "NumpadAdd",
+    "NumpadDivide",
+    "NumpadDecimal",
+    "NumpadComma",
+    "NumpadEnter",
+    "NumpadEquals",
+    "NumpadMultiply",
+    "NumpadSubtract",
+    "AbntC1",
+    "AbntC2",
+    "Apostrophe",
+    "Apps",
+    "Asterisk",
+    "At",
+    "Ax",
+    "Backslash",
+    "Calculator",
+    "Capital",
+    "Colon",
+    "Comma",
+    "Convert",
+    "Equals",
+    "Grave",
+    "Kana",
+    "Kanji",
+    "LAlt",
+    "LBracket",
+    "LControl",
+    "LShift",
+    "LWin",
+    "Mail",
+    "MediaSelect",
+    "MediaStop",
+    "Minus",
+    "Mute",
+    "MyComputer",
+    "NavigateForward",
+    "NavigateBackward",
+    "NextTrack",
+    "NoConvert",
+    "OEM102",
+    "Period",
+    "PlayPause",
+    "Plus",
+    "Power",
+    "PrevTrack",
+    "RAlt",
+    "RBracket",
+    "RControl",
+    "RShift",
+    "RWin",
+    "Semicolon",
+    "Slash",
+    "Sleep",
+    "Stop",
+    "Sysrq",
+    "Tab",
+    "Underline",
+    "Unlabeled",
+    "VolumeDown",
+    "VolumeUp",
+    "Wake",
+    "WebBack",
+    "WebFavorites",
+    "WebForward",
+    "WebHome",
+    "WebRefresh",
+    "WebSearch",
+    "WebStop",
+    "Yen",
+    "Copy",
+    "Paste",
+    "Cut",
+];
+
+/// Maps keyboard keys to controller buttons, so that the
+/// application can be used without a gamepad.
+///
+/// Keys are identified by the name of their winit VirtualKeyCode ("Return", "Escape", "A"...).
+#[derive(Debug, Clone)]
+pub struct KeyboardMapping {
+    keys: HashMap<String, ControllerButton>,
+}
+
+impl Default for KeyboardMapping {
+    fn default() -> Self {
+        let mut mapping = Self::empty();
+        let defaults: [(&[&str], ControllerButton); 12] = [
+            (&["Return", "NumpadEnter"], ControllerButton::A),
+            (&["Escape", "Back"], ControllerButton::B),
+            (&["X"], ControllerButton::X),
+            (&["Y"], ControllerButton::Y),
+            (&["Up"], ControllerButton::Up),
+            (&["Down"], ControllerButton::Down),
+            (&["Left"], ControllerButton::Left),
+            (&["Right"], ControllerButton::Right),
+            (&["Q"], ControllerButton::L),
+            (&["E"], ControllerButton::R),
+            (&["Space"], ControllerButton::Start),
+            (&["Tab"], ControllerButton::Select),
+        ];
+
+        for (keys, button) in defaults {
+            for key in keys {
+                mapping.map(key, button);
+            }
+        }
+
+        mapping
+    }
+}
+
+impl KeyboardMapping {
+    /**
+     * Returns a mapping without any key.
+     */
+    pub fn empty() -> Self {
+        Self {
+            keys: HashMap::new(),
+        }
+    }
+
+    /**
+     * Loads the default mapping, then replaces the keys of every button
+     * listed in the given JSON file:
+     *
+     * { "a": ["Return", "Space"], "b": ["Escape"] }
+     */
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("unable to read input config {:?}", path))?;
+        let mut mapping = Self::default();
+        mapping
+            .apply_config(&content)
+            .with_context(|| format!("invalid input config {:?}", path))?;
+        Ok(mapping)
+    }
+
+    fn apply_config(&mut self, content: &str) -> anyhow::Result<()> {
+        let config: Value = serde_json::from_str(content)?;
+        let buttons = match config {
+            Value::Object(buttons) => buttons,
+            _ => bail!("expected an object mapping buttons to keys"),
+        };
+
+        let mut mapped = vec![];
+        for (name, keys) in buttons {
+            let button = match ControllerButton::from_name(&name) {
+                Some(button) => button,
+                None => bail!("unknown button \"{}\"", name),
+            };
+
+            let keys = match keys {
+                Value::Array(keys) => keys,
+                _ => bail!("\"{}\" must be an array of key names", name),
+            };
+
+            let mut names = vec![];
+            for key in keys {
+                match key {
+                    Value::String(key) if KEY_NAMES.contains(&key.as_str()) => names.push(key),
+                    Value::String(key) => bail!("unknown key \"{}\" for \"{}\"", key, name),
+                    _ => bail!("\"{}\" must be an array of key names", name),
+                }
+            }
+            mapped.push((button, names));
+        }
+
+        // Only changed once the whole config is valid
+        for (button, names) in mapped {
+            self.unmap_button(button);
+            for name in names {
+                self.map(&name, button);
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Maps the key with the given name to the button. A key
+     * can only be mapped to one button.
+     */
+    pub fn map(&mut self, key: &str, button: ControllerButton) {
+        self.keys.insert(key.into(), button);
+    }
+
+    /**
+     * Removes every key mapped to the button.
+     */
+    pub fn unmap_button(&mut self, button: ControllerButton) {
+        self.keys.retain(|_, mapped| *mapped != button);
+    }
+
+    pub fn button(&self, key: VirtualKeyCode) -> Option<ControllerButton> {
+        self.button_by_name(&format!("{:?}", key))
+    }
+
+    pub fn button_by_name(&self, key: &str) -> Option<ControllerButton> {
+        self.keys.get(key).copied()
+    }
+
+    /**
+     * Returns the names of the keys mapped to the button, sorted.
+     */
+    pub fn keys(&self, button: ControllerButton) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .keys
+            .iter()
+            .filter(|(_, mapped)| **mapped == button)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// Analog axes of a controller, from -1.0 (left or up) to 1.0 (right or down).
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum ControllerAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+/// The buttons held and the position of the sticks of a controller.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ControllerState {
+    buttons: [bool; ControllerButton::ALL.len()],
+    axes: [f32; 4],
+}
+
+impl ControllerState {
+    pub fn is_pressed(&self, button: ControllerButton) -> bool {
+        self.buttons[button.index()]
+    }
+
+    pub fn set_pressed(&mut self, button: ControllerButton, pressed: bool) {
+        self.buttons[button.index()] = pressed;
+    }
+
+    pub fn axis(&self, axis: ControllerAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn set_axis(&mut self, axis: ControllerAxis, value: f32) {
+        self.axes[axis as usize] = value.clamp(-1.0, 1.0);
+    }
+
+    /**
+     * Adds the buttons of the other state to this one. For each axis,
+     * the stick pushed the furthest wins.
+     */
+    pub fn merge(&mut self, other: &ControllerState) {
+        for (button, pressed) in self.buttons.iter_mut().zip(other.buttons) {
+            *button |= pressed;
+        }
+        for (axis, value) in self.axes.iter_mut().zip(other.axes) {
+            if value.abs() > axis.abs() {
+                *axis = value;
+            }
+        }
+    }
+
+    /**
+     * Presses the D-pad buttons the left stick is pushed towards.
+     */
+    pub fn apply_left_stick_to_dpad(&mut self) {
+        let x = self.axis(ControllerAxis::LeftX);
+        let y = self.axis(ControllerAxis::LeftY);
+        self.buttons[ControllerButton::Left.index()] |= x <= -STICK_DPAD_THRESHOLD;
+        self.buttons[ControllerButton::Right.index()] |= x >= STICK_DPAD_THRESHOLD;
+        self.buttons[ControllerButton::Up.index()] |= y <= -STICK_DPAD_THRESHOLD;
+        self.buttons[ControllerButton::Down.index()] |= y >= STICK_DPAD_THRESHOLD;
+    }
+}
+
+/// A source of controller input, polled by the application once per frame,
+/// see Application::add_input_backend(). VirtualGamepad is the only one
+/// provided for now, the gamepads of a platform can be added by implementing it.
+pub trait InputBackend {
+    /**
+     * Writes the current state of the controller to the given state,
+     * which is empty. now is the time of the frame in microseconds.
+     */
+    fn update_controller_state(&mut self, now: Time, state: &mut ControllerState);
+}
+
+/// Turns the successive states of a controller into button presses:
+/// one when a button goes down, then, for the D-pad only, one every
+/// repeat interval once it has been held for the repeat delay.
+#[derive(Debug, Clone)]
+pub struct ButtonRepeater {
+    delay: Time,
+    interval: Time,
+    next_repeat: [Option<Time>; ControllerButton::ALL.len()],
+}
+
+impl Default for ButtonRepeater {
+    fn default() -> Self {
+        Self {
+            delay: BUTTON_REPEAT_DELAY,
+            interval: BUTTON_REPEAT_INTERVAL,
+            next_repeat: [None; ControllerButton::ALL.len()],
+        }
+    }
+}
+
+impl ButtonRepeater {
+    /**
+     * Sets the time in ms to hold a D-pad button before it repeats,
+     * and the time in ms between two repeats.
+     */
+    pub fn set_timing(&mut self, delay: Time, interval: Time) {
+        self.delay = delay;
+        self.interval = interval.max(1);
+    }
+
+    /**
+     * Returns the buttons pressed since the previous state, and the ones
+     * repeating. now is the time of the state in microseconds.
+     */
+    pub fn update(&mut self, state: &ControllerState, now: Time) -> Vec<ControllerButton> {
+        let mut pressed = vec![];
+        for button in ControllerButton::ALL {
+            let next_repeat = &mut self.next_repeat[button.index()];
+            if !state.is_pressed(button) {
+                *next_repeat = None;
+                continue;
+            }
+
+            match *next_repeat {
+                None => {
+                    pressed.push(button);
+                    *next_repeat = Some(now + self.delay * 1000);
+                }
+                Some(time) if now >= time && button.focus_direction().is_some() => {
+                    pressed.push(button);
+                    *next_repeat = Some(time + self.interval * 1000);
+                }
+                Some(_) => {}
+            }
+        }
+        pressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ButtonRepeater, ControllerButton, ControllerState, KeyboardMapping, KEY_NAMES};
+    use winit::event::VirtualKeyCode;
+
+    fn holding(buttons: &[ControllerButton]) -> ControllerState {
+        let mut state = ControllerState::default();
+        for button in buttons {
+            state.set_pressed(*button, true);
+        }
+        state
+    }
+
+    #[test]
+    fn repeater_fires_once_when_pressed() {
+        let mut repeater = ButtonRepeater::default();
+        let state = holding(&[ControllerButton::A, ControllerButton::Down]);
+
+        assert_eq!(
+            repeater.update(&state, 0),
+            vec![ControllerButton::A, ControllerButton::Down]
+        );
+        assert!(repeater.update(&state, 16_000).is_empty());
+
+        // Pressed again once released
+        assert!(repeater
+            .update(&ControllerState::default(), 32_000)
+            .is_empty());
+        assert_eq!(repeater.update(&state, 48_000).len(), 2);
+    }
+
+    #[test]
+    fn repeater_repeats_dpad_buttons() {
+        let mut repeater = ButtonRepeater::default();
+        let state = holding(&[ControllerButton::Down]);
+
+        assert_eq!(repeater.update(&state, 0), vec![ControllerButton::Down]);
+        assert!(repeater.update(&state, 499_000).is_empty());
+        assert_eq!(
+            repeater.update(&state, 500_000),
+            vec![ControllerButton::Down]
+        );
+        assert!(repeater.update(&state, 599_000).is_empty());
+        assert_eq!(
+            repeater.update(&state, 600_000),
+            vec![ControllerButton::Down]
+        );
+        assert_eq!(
+            repeater.update(&state, 700_000),
+            vec![ControllerButton::Down]
+        );
+    }
+
+    #[test]
+    fn repeater_does_not_repeat_other_buttons() {
+        let mut repeater = ButtonRepeater::default();
+        let state = holding(&[ControllerButton::A]);
+
+        assert_eq!(repeater.update(&state, 0), vec![ControllerButton::A]);
+        for time in (100_000..2_000_000).step_by(100_000) {
+            assert!(repeater.update(&state, time).is_empty());
+        }
+    }
+
+    #[test]
+    fn default_mapping() {
+        let mapping = KeyboardMapping::default();
+        assert_eq!(
+            mapping.button(VirtualKeyCode::Return),
+            Some(ControllerButton::A)
+        );
+        assert_eq!(
+            mapping.button(VirtualKeyCode::Escape),
+            Some(ControllerButton::B)
+        );
+        assert_eq!(
+            mapping.button(VirtualKeyCode::Up),
+            Some(ControllerButton::Up)
+        );
+        assert_eq!(
+            mapping.button(VirtualKeyCode::Tab),
+            Some(ControllerButton::Select)
+        );
+        assert_eq!(mapping.button(VirtualKeyCode::F1), None);
+        assert_eq!(
+            mapping.keys(ControllerButton::A),
+            vec!["NumpadEnter", "Return"]
+        );
+
+        for button in ControllerButton::ALL {
+            for key in mapping.keys(button) {
+                assert!(KEY_NAMES.contains(&key), "{}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn config_replaces_only_the_listed_buttons() {
+        let mut mapping = KeyboardMapping::default();
+        mapping
+            .apply_config(r#"{ "a": ["Space", "J"], "b": [] }"#)
+            .unwrap();
+
+        assert_eq!(mapping.keys(ControllerButton::A), vec!["J", "Space"]);
+        assert!(mapping.keys(ControllerButton::B).is_empty());
+        // A key is only mapped to one button
+        assert!(mapping.keys(ControllerButton::Start).is_empty());
+        assert_eq!(mapping.keys(ControllerButton::Select), vec!["Tab"]);
+        assert_eq!(mapping.button(VirtualKeyCode::Return), None);
+    }
+
+    #[test]
+    fn config_rejects_unknown_keys() {
+        let mut mapping = KeyboardMapping::default();
+        let error = mapping
+            .apply_config(r#"{ "b": ["Back"], "a": ["Retrun"] }"#)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "unknown key \"Retrun\" for \"a\"");
+        // Left as it was, so that the keyboard still works
+        assert_eq!(
+            mapping.keys(ControllerButton::A),
+            vec!["NumpadEnter", "Return"]
+        );
+        assert_eq!(mapping.keys(ControllerButton::B), vec!["Back", "Escape"]);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::core::view_creator::{XMLDiagnostic, XMLParser, XMLViewRegistry};
+use anyhow::Context;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Result of linting a directory.
+pub struct LintReport {
+    /// Every XML file checked, sorted
+    pub files: Vec<PathBuf>,
+    pub diagnostics: Vec<XMLDiagnostic>,
+}
+
+/**
+ * Inflates every XML file found in the directory and its subdirectories,
+ * using the same parser as ViewCreator, and returns every problem found.
+ *
+ * Does not need a window or a GL context. Problems of included files
+ * are only reported once, even if they are included several times.
+ */
+pub fn lint_directory(
+    registry: &XMLViewRegistry,
+    directory: &Path,
+) -> anyhow::Result<LintReport> {
+    let mut files = vec![];
+    find_xml_files(directory, &mut files)?;
+    files.sort();
+
+    let mut seen = HashSet::new();
+    let mut diagnostics = vec![];
+
+    for file in &files {
+        let source = fs::read_to_string(file)
+            .with_context(|| format!("unable to read XML file {:?}", file))?;
+
+        if let Err(error) = XMLParser::new(registry, file.clone(), &source).parse() {
+            for diagnostic in error.diagnostics {
+                if seen.insert(diagnostic.clone()) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+    }
+
+    Ok(LintReport { files, diagnostics })
+}
+
+fn find_xml_files(directory: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("unable to read directory {:?}", directory))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_xml_files(&path, files)?;
+        } else if path.extension().map_or(false, |extension| extension == "xml") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::i18n::add_translation;
+    use crate::core::temp_dir::TempDir;
+
+    fn lint(dir: &Path) -> Vec<String> {
+        let report = lint_directory(&XMLViewRegistry::new(), dir).unwrap();
+        report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let file = diagnostic.file.strip_prefix(dir).unwrap();
+                format!(
+                    "{}:{}: {}",
+                    file.display(),
+                    diagnostic.line,
+                    diagnostic.message
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_problem_with_its_position() {
+        add_translation("lint/title", "Title");
+        let dir = TempDir::with_files(
+            "lint-problems",
+            &[
+                (
+                    "xml/main.xml",
+                    r#"<brls:Box>
+    <brls:Labl/>
+    <brls:Label txt="Hello"/>
+    <brls:Label width="12px"/>
+    <brls:Label text="@i18n/lint/title"/>
+    <brls:Label text="@i18n/lint/missing"/>
+</brls:Box>"#,
+                ),
+                (
+                    "xml/views/cell.xml",
+                    r#"<brls:Box id="cell">
+    <brls:Label id="title"/>
+    <brls:Label id="title"/>
+</brls:Box>"#,
+                ),
+                ("xml/notes.txt", "<brls:Labl/>"),
+            ],
+        );
+
+        assert_eq!(
+            lint(dir.path()),
+            vec![
+                "xml/main.xml:2: unknown XML tag \"brls:Labl\"",
+                "xml/main.xml:3: <brls:Label>: unknown attribute \"txt\"",
+                "xml/main.xml:4: <brls:Label>: invalid value \"12px\" for attribute \"width\", \
+                 expected \"auto\" or a percentage or a number",
+                "xml/main.xml:6: <brls:Label>: unresolved reference \"@i18n/lint/missing\": \
+                 unknown translation",
+                "xml/views/cell.xml:3: duplicate id \"title\", first defined at line 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_every_xml_file_once() {
+        let dir = TempDir::with_files(
+            "lint-files",
+            &[
+                ("xml/b.xml", "<brls:Box/>"),
+                ("xml/a/a.xml", "<brls:Box/>"),
+                ("xml/a/readme.md", "# not a layout"),
+            ],
+        );
+
+        let report = lint_directory(&XMLViewRegistry::new(), dir.path()).unwrap();
+        assert_eq!(
+            report.files,
+            vec![dir.join("xml/a/a.xml"), dir.join("xml/b.xml")]
+        );
+        assert!(report.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn missing_directory_is_an_error() {
+        let dir = TempDir::new("lint-missing");
+        let missing = dir.join("missing");
+        assert!(lint_directory(&XMLViewRegistry::new(), &missing).is_err());
+    }
+}
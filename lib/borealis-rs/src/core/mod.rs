@@ -2,11 +2,15 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+pub mod actions;
 pub mod activity;
 pub mod application;
 pub mod font;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
 pub mod frame_context;
 pub mod geometry;
+pub mod gesture;
 pub mod global;
 pub mod platform;
 pub mod style;
@@ -22,6 +26,16 @@ pub mod animation;
 pub mod time;
 pub mod tweening;
 pub mod bind;
+pub mod attribute;
+pub mod i18n;
+pub mod input;
+pub mod lint;
+pub mod recording;
+pub mod resource;
+#[cfg(test)]
+pub mod temp_dir;
+pub mod transition;
+pub mod virtual_gamepad;
 
 use nanovg::{Color, Context, PathOptions};
 use raw_window_handle::{
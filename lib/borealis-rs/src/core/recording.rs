@@ -0,0 +1,452 @@
+use crate::core::geometry::Point;
+use crate::core::gesture::TouchPhase;
+use crate::core::input::ControllerButton;
+use crate::core::time::{Clock, ManualClock, Time};
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Version of the recording files written by InputRecording::save().
+pub const RECORDING_VERSION: u64 = 1;
+
+/// Time in microseconds between two frames of a replay.
+pub const REPLAY_FRAME_DURATION: Time = 16_667;
+
+/// An input event once translated from the platform events, as handled
+/// by Application::handle_input(). Recordings are made of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// A button of a gamepad
+    Button(ControllerButton),
+    /// A keyboard key, by the name of its winit VirtualKeyCode,
+    /// turned into a button by the keyboard mapping.
+    /// Recorded as that button, see InputRecorder::record_key()
+    Key(String),
+    /// A finger, or the mouse while its left button is pressed
+    Touch {
+        finger_id: u64,
+        phase: TouchPhase,
+        position: Point,
+    },
+    /// A finger taken away by the system
+    TouchCancelled { finger_id: u64 },
+    /// The mouse wheel, in lines
+    Scroll { delta: Point },
+}
+
+/// An input event and its time in microseconds since the start of the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub time: Time,
+    pub event: InputEvent,
+    /// Keyboard key a button was pressed with, for information only:
+    /// replays do not depend on the keyboard mapping
+    pub key: Option<String>,
+}
+
+/// The input events received by the application during a session,
+/// to be replayed to reproduce it. Saved as JSON:
+///
+/// { "version": 1, "window": [1280, 720], "events": [
+///     { "time": 1250000, "type": "button", "button": "down", "key": "Down" },
+///     { "time": 1800000, "type": "touch", "phase": "start", "finger": 0, "x": 640.0, "y": 360.0 }
+/// ] }
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    /// Size of the window when the recording started, touches depend on it
+    pub window_size: Option<(u32, u32)>,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("unable to read input recording {:?}", path))?;
+        Self::from_json(&content).with_context(|| format!("invalid input recording {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_json())
+            .with_context(|| format!("unable to write input recording {:?}", path))
+    }
+
+    /**
+     * Returns the time of the last event, which is the duration of the recording.
+     */
+    pub fn duration(&self) -> Time {
+        self.events.last().map_or(0, |event| event.time)
+    }
+
+    pub fn to_json(&self) -> String {
+        let events: Vec<Value> = self.events.iter().map(event_to_json).collect();
+        let mut recording = json!({
+            "version": RECORDING_VERSION,
+            "events": events,
+        });
+        if let Some((width, height)) = self.window_size {
+            recording["window"] = json!([width, height]);
+        }
+        recording.to_string()
+    }
+
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        let recording: Value = serde_json::from_str(content)?;
+        let version = recording["version"].as_u64();
+        if version != Some(RECORDING_VERSION) {
+            bail!("unsupported version {}", recording["version"]);
+        }
+
+        let window_size = match &recording["window"] {
+            Value::Null => None,
+            window => match (window[0].as_u64(), window[1].as_u64()) {
+                (Some(width), Some(height)) => Some((width as u32, height as u32)),
+                _ => bail!("\"window\" must be [width, height]"),
+            },
+        };
+
+        let events = match &recording["events"] {
+            Value::Array(events) => events,
+            _ => bail!("\"events\" must be an array"),
+        };
+
+        let mut recording = Self {
+            window_size,
+            events: Vec::with_capacity(events.len()),
+        };
+        for (index, event) in events.iter().enumerate() {
+            let event = event_from_json(event).with_context(|| format!("event {}", index))?;
+            if event.time < recording.duration() {
+                bail!("event {} is older than the previous one", index);
+            }
+            recording.events.push(event);
+        }
+
+        Ok(recording)
+    }
+}
+
+fn touch_phase_name(phase: TouchPhase) -> &'static str {
+    match phase {
+        TouchPhase::Start => "start",
+        TouchPhase::Stay => "stay",
+        TouchPhase::End => "end",
+    }
+}
+
+fn event_to_json(recorded: &RecordedEvent) -> Value {
+    let mut event = match &recorded.event {
+        InputEvent::Button(button) => json!({ "type": "button", "button": button.name() }),
+        InputEvent::Key(key) => json!({ "type": "key", "key": key }),
+        InputEvent::Touch {
+            finger_id,
+            phase,
+            position,
+        } => json!({
+            "type": "touch",
+            "phase": touch_phase_name(*phase),
+            "finger": finger_id,
+            "x": position.x,
+            "y": position.y,
+        }),
+        InputEvent::TouchCancelled { finger_id } => {
+            json!({ "type": "touch", "phase": "cancelled", "finger": finger_id })
+        }
+        InputEvent::Scroll { delta } => json!({ "type": "scroll", "x": delta.x, "y": delta.y }),
+    };
+    event["time"] = json!(recorded.time as u64);
+    if let Some(key) = &recorded.key {
+        event["key"] = json!(key);
+    }
+    event
+}
+
+fn event_from_json(event: &Value) -> anyhow::Result<RecordedEvent> {
+    let event = match event {
+        Value::Object(event) => event,
+        _ => bail!("expected an object"),
+    };
+
+    let time = match event.get("time").and_then(Value::as_u64) {
+        Some(time) => time as Time,
+        None => bail!("missing \"time\""),
+    };
+
+    let mut key = None;
+    let event = match string_field(event, "type")? {
+        "button" => {
+            let name = string_field(event, "button")?;
+            key = event.get("key").and_then(Value::as_str).map(String::from);
+            match ControllerButton::from_name(name) {
+                Some(button) => InputEvent::Button(button),
+                None => bail!("unknown button \"{}\"", name),
+            }
+        }
+        "key" => InputEvent::Key(string_field(event, "key")?.into()),
+        "touch" => {
+            let finger_id = match event.get("finger").and_then(Value::as_u64) {
+                Some(finger_id) => finger_id,
+                None => bail!("missing \"finger\""),
+            };
+            let phase = match string_field(event, "phase")? {
+                "start" => TouchPhase::Start,
+                "stay" => TouchPhase::Stay,
+                "end" => TouchPhase::End,
+                "cancelled" => {
+                    return Ok(RecordedEvent {
+                        time,
+                        event: InputEvent::TouchCancelled { finger_id },
+                        key: None,
+                    })
+                }
+                phase => bail!("unknown touch phase \"{}\"", phase),
+            };
+            InputEvent::Touch {
+                finger_id,
+                phase,
+                position: point_fields(event)?,
+            }
+        }
+        "scroll" => InputEvent::Scroll {
+            delta: point_fields(event)?,
+        },
+        event_type => bail!("unknown event type \"{}\"", event_type),
+    };
+
+    Ok(RecordedEvent { time, event, key })
+}
+
+fn string_field<'a>(event: &'a Map<String, Value>, name: &str) -> anyhow::Result<&'a str> {
+    match event.get(name).and_then(Value::as_str) {
+        Some(value) => Ok(value),
+        None => bail!("missing \"{}\"", name),
+    }
+}
+
+fn point_fields(event: &Map<String, Value>) -> anyhow::Result<Point> {
+    match (
+        event.get("x").and_then(Value::as_f64),
+        event.get("y").and_then(Value::as_f64),
+    ) {
+        (Some(x), Some(y)) => Ok(Point::new(x as f32, y as f32)),
+        _ => bail!("missing \"x\" or \"y\""),
+    }
+}
+
+/// Records the input events handled by the application,
+/// timed with the clock of the application.
+pub struct InputRecorder {
+    start_time: Time,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    pub fn new(start_time: Time, window_size: Option<(u32, u32)>) -> Self {
+        Self {
+            start_time,
+            recording: InputRecording {
+                window_size,
+                events: vec![],
+            },
+        }
+    }
+
+    pub fn record(&mut self, now: Time, event: InputEvent) {
+        self.recording.events.push(RecordedEvent {
+            time: now.saturating_sub(self.start_time),
+            event,
+            key: None,
+        });
+    }
+
+    /**
+     * Records a keyboard key as the button it is mapped to, so that
+     * the replay does not depend on the keyboard mapping of the machine.
+     * The key is kept for information.
+     */
+    pub fn record_key(&mut self, now: Time, button: ControllerButton, key: String) {
+        self.recording.events.push(RecordedEvent {
+            time: now.saturating_sub(self.start_time),
+            event: InputEvent::Button(button),
+            key: Some(key),
+        });
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Gives back the events of a recording frame by frame, moving a virtual
+/// clock by REPLAY_FRAME_DURATION at each frame. Replays do not depend
+/// on the speed of the machine, and do not need a window.
+pub struct InputPlayer {
+    recording: InputRecording,
+    clock: ManualClock,
+    start_time: Time,
+    frame_time: Time,
+    next_event: usize,
+}
+
+impl InputPlayer {
+    /**
+     * Creates a player starting at the current time of the clock.
+     */
+    pub fn new(recording: InputRecording, clock: ManualClock) -> Self {
+        Self {
+            recording,
+            start_time: clock.now_usec(),
+            clock,
+            frame_time: 0,
+            next_event: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /**
+     * Moves to the next frame and returns the events received before it.
+     * The clock stays at the frame time, see seek() to handle each event at its own time.
+     */
+    pub fn next_frame(&mut self) -> Vec<RecordedEvent> {
+        self.frame_time += REPLAY_FRAME_DURATION;
+        let events: Vec<RecordedEvent> = self.recording.events[self.next_event..]
+            .iter()
+            .take_while(|event| event.time <= self.frame_time)
+            .cloned()
+            .collect();
+        self.next_event += events.len();
+        self.seek(self.frame_time);
+        events
+    }
+
+    /**
+     * Sets the clock to the given time since the start of the replay.
+     */
+    pub fn seek(&self, time: Time) {
+        self.clock.set_now_usec(self.start_time + time);
+    }
+
+    /**
+     * Returns the time of the current frame since the start of the replay.
+     */
+    pub fn frame_time(&self) -> Time {
+        self.frame_time
+    }
+
+    /**
+     * Returns true once every event has been given back.
+     */
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.recording.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputEvent, InputPlayer, InputRecorder, InputRecording, RecordedEvent};
+    use crate::core::geometry::Point;
+    use crate::core::gesture::TouchPhase;
+    use crate::core::input::ControllerButton;
+    use crate::core::time::{Clock, ManualClock};
+
+    fn event(time: u128, event: InputEvent) -> RecordedEvent {
+        RecordedEvent {
+            time,
+            event,
+            key: None,
+        }
+    }
+
+    fn touch(finger_id: u64, phase: TouchPhase) -> InputEvent {
+        InputEvent::Touch {
+            finger_id,
+            phase,
+            position: Point::new(640.5, 360.25),
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut recorder = InputRecorder::new(1_000, Some((1280, 720)));
+        recorder.record_key(2_000, ControllerButton::A, "Return".into());
+        let mut recording = recorder.finish();
+        recording.events.extend([
+            event(1_500, InputEvent::Button(ControllerButton::Down)),
+            event(2_000, InputEvent::Key("Escape".into())),
+            event(3_000, touch(0, TouchPhase::Start)),
+            event(3_000, touch(0, TouchPhase::Stay)),
+            event(4_000, touch(0, TouchPhase::End)),
+            event(5_000, InputEvent::TouchCancelled { finger_id: 1 }),
+            event(
+                6_000,
+                InputEvent::Scroll {
+                    delta: Point::new(0.0, -2.0),
+                },
+            ),
+        ]);
+
+        let loaded = InputRecording::from_json(&recording.to_json()).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.events[0].key.as_deref(), Some("Return"));
+        assert_eq!(loaded.duration(), 6_000);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let error = InputRecording::from_json(r#"{ "version": 2, "events": [] }"#).unwrap_err();
+        assert_eq!(error.to_string(), "unsupported version 2");
+        assert!(InputRecording::from_json(r#"{ "events": [] }"#).is_err());
+    }
+
+    #[test]
+    fn rejects_events_out_of_order() {
+        let json = r#"{ "version": 1, "events": [
+            { "time": 2000, "type": "button", "button": "a" },
+            { "time": 1000, "type": "button", "button": "b" }
+        ] }"#;
+        let error = InputRecording::from_json(json).unwrap_err();
+        assert_eq!(error.to_string(), "event 1 is older than the previous one");
+    }
+
+    #[test]
+    fn player_gives_events_at_their_frame() {
+        let recording = InputRecording {
+            window_size: None,
+            events: vec![
+                event(10_000, InputEvent::Button(ControllerButton::A)),
+                event(16_667, InputEvent::Button(ControllerButton::B)),
+                event(40_000, InputEvent::Button(ControllerButton::X)),
+            ],
+        };
+        let clock = ManualClock::new(5_000_000);
+        let mut player = InputPlayer::new(recording, clock.clone());
+
+        let frame = player.next_frame();
+        assert_eq!(frame.len(), 2);
+        assert_eq!(frame[1].event, InputEvent::Button(ControllerButton::B));
+        assert_eq!(clock.now_usec(), 5_016_667);
+
+        assert!(player.next_frame().is_empty());
+        assert!(!player.is_finished());
+
+        let frame = player.next_frame();
+        assert_eq!(frame.len(), 1);
+        assert_eq!(player.frame_time(), 50_001);
+        assert!(player.is_finished());
+
+        // Events are handled at their own time within the frame
+        player.seek(frame[0].time);
+        assert_eq!(clock.now_usec(), 5_040_000);
+    }
+}
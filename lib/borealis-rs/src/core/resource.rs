@@ -0,0 +1,343 @@
+use crate::core::i18n::try_translation;
+use crate::core::style::try_style;
+use crate::core::theme::try_theme;
+use nanovg_sys::NVGcolor;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Directory holding the application resources (XML layouts, images...),
+/// relative to the working directory.
+pub const CUSTOM_RESOURCES_PATH: &str = "resources";
+
+lazy_static! {
+    static ref GLOBAL_RESOURCES_PATH: Mutex<PathBuf> =
+        Mutex::new(PathBuf::from(CUSTOM_RESOURCES_PATH));
+}
+
+/**
+ * Returns the directory "@res/" references are resolved from.
+ * Default is CUSTOM_RESOURCES_PATH.
+ */
+pub fn resources_path() -> PathBuf {
+    GLOBAL_RESOURCES_PATH.lock().unwrap().clone()
+}
+
+pub fn set_resources_path(path: PathBuf) {
+    *GLOBAL_RESOURCES_PATH.lock().unwrap() = path;
+}
+
+/// A reference to a resource, as written in XML files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResourceReference<'a> {
+    /// "@style/key", a metric of core::style
+    Style(&'a str),
+    /// "@theme/key", a color of the selected core::theme
+    Theme(&'a str),
+    /// "@i18n/key", a translated string
+    I18n(&'a str),
+    /// "@res/path", a file inside the resources directory
+    Res(&'a str),
+}
+
+impl<'a> ResourceReference<'a> {
+    /**
+     * Parses a reference, returns None if the value
+     * does not start with one of the known prefixes.
+     */
+    pub fn parse(value: &'a str) -> Option<Self> {
+        if let Some(key) = value.strip_prefix("@style/") {
+            Some(ResourceReference::Style(key))
+        } else if let Some(key) = value.strip_prefix("@theme/") {
+            Some(ResourceReference::Theme(key))
+        } else if let Some(key) = value.strip_prefix("@i18n/") {
+            Some(ResourceReference::I18n(key))
+        } else if let Some(path) = value.strip_prefix("@res/") {
+            Some(ResourceReference::Res(path))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ResourceReference<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceReference::Style(key) => write!(f, "@style/{}", key),
+            ResourceReference::Theme(key) => write!(f, "@theme/{}", key),
+            ResourceReference::I18n(key) => write!(f, "@i18n/{}", key),
+            ResourceReference::Res(path) => write!(f, "@res/{}", path),
+        }
+    }
+}
+
+/// A reference that could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedResource {
+    pub reference: String,
+    pub message: String,
+}
+
+impl UnresolvedResource {
+    fn new(reference: &str, message: impl Into<String>) -> Self {
+        Self {
+            reference: reference.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for UnresolvedResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unresolved reference \"{}\": {}", self.reference, self.message)
+    }
+}
+
+impl Error for UnresolvedResource {}
+
+/**
+ * Resolves a "@style/" reference to its metric.
+ */
+pub fn resolve_style(reference: &str) -> Result<f32, UnresolvedResource> {
+    match ResourceReference::parse(reference) {
+        Some(ResourceReference::Style(key)) => {
+            try_style(key).ok_or_else(|| UnresolvedResource::new(reference, "unknown style"))
+        }
+        _ => Err(UnresolvedResource::new(reference, "expected a @style/ reference")),
+    }
+}
+
+/**
+ * Resolves a "@theme/" reference to its color in the selected theme.
+ */
+pub fn resolve_theme(reference: &str) -> Result<NVGcolor, UnresolvedResource> {
+    match ResourceReference::parse(reference) {
+        Some(ResourceReference::Theme(key)) => try_theme(key)
+            .ok_or_else(|| UnresolvedResource::new(reference, "unknown theme color")),
+        _ => Err(UnresolvedResource::new(reference, "expected a @theme/ reference")),
+    }
+}
+
+/**
+ * Resolves an "@i18n/" reference to its translation.
+ */
+pub fn resolve_i18n(reference: &str) -> Result<String, UnresolvedResource> {
+    match ResourceReference::parse(reference) {
+        Some(ResourceReference::I18n(key)) => try_translation(key)
+            .ok_or_else(|| UnresolvedResource::new(reference, "unknown translation")),
+        _ => Err(UnresolvedResource::new(reference, "expected an @i18n/ reference")),
+    }
+}
+
+/**
+ * Resolves a "@res/" reference to the path of the file
+ * inside the resources directory. The file has to exist.
+ */
+pub fn resolve_res(reference: &str) -> Result<PathBuf, UnresolvedResource> {
+    match ResourceReference::parse(reference) {
+        Some(ResourceReference::Res(path)) => {
+            let path = resources_path().join(path);
+            match path.exists() {
+                true => Ok(path),
+                false => Err(UnresolvedResource::new(
+                    reference,
+                    format!("file \"{}\" not found", path.display()),
+                )),
+            }
+        }
+        _ => Err(UnresolvedResource::new(reference, "expected a @res/ reference")),
+    }
+}
+
+/// Resolves resource references from Rust code, collecting the ones
+/// that cannot be resolved instead of failing on the first one.
+///
+/// Call unresolved() once done to report every problem at once.
+#[derive(Debug, Default)]
+pub struct ResourceResolver {
+    unresolved: Vec<UnresolvedResource>,
+}
+
+impl ResourceResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(&mut self, reference: &str) -> Option<f32> {
+        self.collect(resolve_style(reference))
+    }
+
+    pub fn theme(&mut self, reference: &str) -> Option<NVGcolor> {
+        self.collect(resolve_theme(reference))
+    }
+
+    /**
+     * Returns the translation of an "@i18n/" reference, or the
+     * value as is if it is a plain string or cannot be resolved.
+     */
+    pub fn text(&mut self, value: &str) -> String {
+        match ResourceReference::parse(value) {
+            Some(ResourceReference::I18n(_)) => self
+                .collect(resolve_i18n(value))
+                .unwrap_or_else(|| value.into()),
+            _ => value.into(),
+        }
+    }
+
+    /**
+     * Returns the path of a "@res/" reference, or the
+     * value as a path if it is not a reference.
+     */
+    pub fn path(&mut self, value: &str) -> Option<PathBuf> {
+        match ResourceReference::parse(value) {
+            Some(ResourceReference::Res(_)) => self.collect(resolve_res(value)),
+            _ => Some(PathBuf::from(value)),
+        }
+    }
+
+    /**
+     * Returns every reference that could not be resolved so far.
+     */
+    pub fn unresolved(&self) -> &[UnresolvedResource] {
+        &self.unresolved
+    }
+
+    pub fn take_unresolved(&mut self) -> Vec<UnresolvedResource> {
+        std::mem::take(&mut self.unresolved)
+    }
+
+    fn collect<T>(&mut self, result: Result<T, UnresolvedResource>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(unresolved) => {
+                warn!("{}", unresolved);
+                self.unresolved.push(unresolved);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::i18n::add_translation;
+    use crate::core::style::add_style;
+    use crate::core::theme::theme;
+
+    fn message<T>(result: Result<T, UnresolvedResource>) -> String {
+        result.err().unwrap().message
+    }
+
+    #[test]
+    fn references_are_parsed_by_prefix() {
+        for (value, reference) in [
+            ("@style/a/b", ResourceReference::Style("a/b")),
+            ("@theme/a/b", ResourceReference::Theme("a/b")),
+            ("@i18n/a/b", ResourceReference::I18n("a/b")),
+            ("@res/a/b.png", ResourceReference::Res("a/b.png")),
+        ] {
+            assert_eq!(ResourceReference::parse(value), Some(reference));
+            assert_eq!(reference.to_string(), value);
+        }
+        assert_eq!(ResourceReference::parse("plain"), None);
+        assert_eq!(ResourceReference::parse("@color/a"), None);
+    }
+
+    #[test]
+    fn style_reference_resolves_to_its_metric() {
+        add_style("resource/margin", 12.0);
+        assert_eq!(resolve_style("@style/resource/margin"), Ok(12.0));
+        assert_eq!(
+            message(resolve_style("@style/resource/missing")),
+            "unknown style"
+        );
+        assert_eq!(
+            message(resolve_style("@theme/brls/text")),
+            "expected a @style/ reference"
+        );
+    }
+
+    #[test]
+    fn theme_reference_resolves_to_its_color() {
+        let color = resolve_theme("@theme/brls/text").unwrap();
+        assert_eq!(color.rgba, theme("brls/text").rgba);
+        assert_eq!(
+            message(resolve_theme("@theme/resource/missing")),
+            "unknown theme color"
+        );
+        assert_eq!(
+            message(resolve_theme("@style/brls/text")),
+            "expected a @theme/ reference"
+        );
+    }
+
+    #[test]
+    fn i18n_reference_resolves_to_its_translation() {
+        add_translation("resource/hello", "Hello");
+        assert_eq!(resolve_i18n("@i18n/resource/hello"), Ok("Hello".into()));
+        assert_eq!(
+            message(resolve_i18n("@i18n/resource/missing")),
+            "unknown translation"
+        );
+        assert_eq!(
+            message(resolve_i18n("hello")),
+            "expected an @i18n/ reference"
+        );
+    }
+
+    #[test]
+    fn res_reference_resolves_to_an_existing_file() {
+        let main = resources_path().join("xml/activity/main.xml");
+        assert_eq!(resolve_res("@res/xml/activity/main.xml"), Ok(main));
+
+        let missing = resources_path().join("img/missing.png");
+        let error = resolve_res("@res/img/missing.png").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "unresolved reference \"@res/img/missing.png\": file \"{}\" not found",
+                missing.display()
+            )
+        );
+        assert_eq!(
+            message(resolve_res("img/missing.png")),
+            "expected a @res/ reference"
+        );
+    }
+
+    #[test]
+    fn resolver_collects_every_failure() {
+        add_style("resource/resolver", 4.0);
+        let mut resolver = ResourceResolver::new();
+
+        assert_eq!(resolver.style("@style/resource/resolver"), Some(4.0));
+        assert_eq!(resolver.style("@style/resource/missing"), None);
+        assert!(resolver.theme("@theme/resource/missing").is_none());
+        assert_eq!(
+            resolver.text("@i18n/resource/missing"),
+            "@i18n/resource/missing"
+        );
+        assert_eq!(resolver.text("plain"), "plain");
+        assert_eq!(resolver.path("@res/img/missing.png"), None);
+        assert_eq!(resolver.path("img/plain.png"), Some("img/plain.png".into()));
+
+        let references: Vec<&str> = resolver
+            .unresolved()
+            .iter()
+            .map(|unresolved| unresolved.reference.as_str())
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                "@style/resource/missing",
+                "@theme/resource/missing",
+                "@i18n/resource/missing",
+                "@res/img/missing.png",
+            ]
+        );
+        assert_eq!(resolver.take_unresolved().len(), 4);
+        assert!(resolver.unresolved().is_empty());
+    }
+}
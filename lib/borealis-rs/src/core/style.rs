@@ -23,6 +23,7 @@ lazy_static! {
             (String::from("brls/highlight/shadow_offset"), 10.0),
             (String::from("brls/highlight/shadow_feather"), 10.0),
             (String::from("brls/highlight/shadow_opacity"), 128.0),
+            (String::from("brls/highlight/shake_amplitude"), 17.0),
 
             // AppletFrame
             (String::from("brls/applet_frame/padding_sides"), 30.0),
@@ -108,6 +109,7 @@ lazy_static! {
             (String::from("brls/hints/footer_margin_sides"), 30.0),
             (String::from("brls/hints/footer_padding_sides"), 25.0),
             (String::from("brls/hints/footer_padding_top_bottom"), 8.0),
+            (String::from("brls/hints/font_size"), 21.5),
 
             // Spinner
             (String::from("brls/spinner/center_gap_multiplier_large"), 0.207),
@@ -134,3 +136,8 @@ pub fn add_style(key: &str, value: f32) {
     let mut map = GLOBAL_STYLE.lock().unwrap();
     map.insert(key.parse().unwrap(), value);
 }
+
+pub fn try_style(key: &str) -> Option<f32> {
+    let map = GLOBAL_STYLE.lock().unwrap();
+    map.get(key).cloned()
+}
@@ -0,0 +1,53 @@
+//! Temporary directories for the tests reading files.
+//!
+//! Only uses std: the integration tests include this file as well.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory of the system temporary directory, named after the test
+/// and the process id, removed with its content when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /**
+     * Creates an empty directory. Two tests of the same process must use
+     * different names, the same test run twice at once gets two directories.
+     */
+    pub fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("borealis-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /**
+     * Creates a directory holding the given files, with their parent directories.
+     * "$DIR" in their content is replaced by the path of the directory.
+     */
+    pub fn with_files(name: &str, files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new(name);
+        for (file, content) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content.replace("$DIR", &dir.path.to_string_lossy())).unwrap();
+        }
+        dir
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
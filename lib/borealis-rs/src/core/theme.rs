@@ -148,6 +148,13 @@ pub fn theme(key: &str) -> NVGcolor {
         .clone()
 }
 
+pub fn try_theme(key: &str) -> Option<NVGcolor> {
+    let map = GLOBAL_THEME.lock().unwrap();
+    map.get(&theme_selected())
+        .and_then(|theme| theme.get(key))
+        .cloned()
+}
+
 pub fn add_theme(key: &str, value: NVGcolor) {
     let mut map = GLOBAL_THEME.lock().unwrap();
     map.get_mut(&theme_selected())
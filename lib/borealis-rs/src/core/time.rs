@@ -1,3 +1,5 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub type Time = u128;
@@ -7,23 +9,131 @@ pub type Time = u128;
  */
 pub fn get_time_usec() -> u128 {
     // 获取当前时间，并转换为微秒
-    let now = SystemTime::now().duration_since(UNIX_EPOCH)
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
         .expect("Time went backwards");
     now.as_micros() // 返回微秒
 }
 
+/// Source of the current time used by the application
+/// to update tickings and compute the FPS.
+pub trait Clock {
+    /**
+     * Returns the current time in microseconds.
+     */
+    fn now_usec(&self) -> Time;
+}
+
+/// The system clock, see get_time_usec().
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now_usec(&self) -> Time {
+        get_time_usec()
+    }
+}
+
+/// A clock that only moves when told to, to make
+/// animations and timers reproducible in tests.
+///
+/// Clones share the same time, so a test can keep one
+/// to advance the clock given to the application.
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<Time>>,
+}
+
+impl ManualClock {
+    pub fn new(now_usec: Time) -> Self {
+        Self {
+            now: Rc::new(Cell::new(now_usec)),
+        }
+    }
+
+    /**
+     * Moves the clock forward by the given delta in ms.
+     */
+    pub fn advance(&self, delta: Time) {
+        self.advance_usec(delta * 1000);
+    }
+
+    pub fn advance_usec(&self, delta: Time) {
+        self.now.set(self.now.get() + delta);
+    }
+
+    pub fn set_now_usec(&self, now_usec: Time) {
+        self.now.set(now_usec);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_usec(&self) -> Time {
+        self.now.get()
+    }
+}
 
 pub type TickingGenericCallback = Box<dyn Fn()>;
 
 pub type TickingEndCallback = Box<dyn Fn(bool)>;
 pub type TickingTickCallback = TickingGenericCallback;
 
+/// Running flag and callbacks of a ticking.
+///
+/// Every field is behind a cell so that tickings can be started, stopped
+/// and updated through a shared reference, including from their own callbacks.
+#[derive(Default)]
+pub struct TickingState {
+    running: Cell<bool>,
+    scheduled: Cell<bool>,
+    end_callback: RefCell<Option<TickingEndCallback>>,
+    tick_callback: RefCell<Option<TickingTickCallback>>,
+}
+
+impl TickingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn call_end_callback(&self, finished: bool) {
+        // Taken out during the call so that the callback can replace itself
+        let callback = self.end_callback.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback(finished);
+            let mut slot = self.end_callback.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+
+    fn call_tick_callback(&self) {
+        let callback = self.tick_callback.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback();
+            let mut slot = self.tick_callback.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+}
 
 /// Interface representing something that "ticks" every frame for a certain amount of frames,
 /// like a timer, an animation, a background task...
 /// The library manages a list of running tickings. Each ticking is reponsible for managing its own
 /// lifetime by returning true or false in onUpdate.
 pub trait Ticking {
+    /**
+     * Returns the running state and callbacks of the ticking.
+     */
+    fn ticking_state(&self) -> &TickingState;
+
+    /**
+     * Returns a new handle sharing the state of this ticking,
+     * kept by the scheduler as long as the ticking runs.
+     */
+    fn ticking_handle(&self) -> Box<dyn Ticking>;
 
     /**
      * Starts the ticking, pushing it to the list of running tickings.
@@ -31,14 +141,30 @@ pub trait Ticking {
      * If the ticking is already running, this method will have no effect.
      */
     fn start(&self) {
+        if self.is_running() {
+            return;
+        }
+
+        let state = self.ticking_state();
+        state.running.set(true);
+        if !state.scheduled.get() {
+            match TickingScheduler::current() {
+                Some(scheduler) => {
+                    state.scheduled.set(true);
+                    scheduler.schedule(self.ticking_handle());
+                }
+                None => warn!("No ticking scheduler, the ticking will not be updated"),
+            }
+        }
 
+        self.on_start();
     }
 
     /**
      * Stops the ticking if it was running, and executes the end callback.
      */
     fn stop(&self) {
-
+        self.stop_finished(false);
     }
 
     /**
@@ -47,8 +173,8 @@ pub trait Ticking {
      * The callback argument will be set to true if the ticking stopped
      * on its own, false if it was stopped early by the user.
      */
-    fn set_end_callback(&mut self, end_callback: TickingEndCallback) {
-
+    fn set_end_callback(&self, end_callback: TickingEndCallback) {
+        *self.ticking_state().end_callback.borrow_mut() = Some(end_callback);
     }
 
     /**
@@ -58,25 +184,36 @@ pub trait Ticking {
      * The last animation tick will execute the tick callback
      * then the end callback.
      */
-    fn set_tick_callback(&mut self, tick_callback: TickingTickCallback) {
-
+    fn set_tick_callback(&self, tick_callback: TickingTickCallback) {
+        *self.ticking_state().tick_callback.borrow_mut() = Some(tick_callback);
     }
 
     /**
      * Returns true if the ticking is currently running.
      */
     fn is_running(&self) -> bool {
-        todo!()
+        self.ticking_state().running.get()
     }
 
     /**
-     * Called internally by the main loop. Takes all running tickings
-     * and updates them.
+     * Advances the ticking by the given delta in ms, executing
+     * the tick callback and stopping it if it finished.
+     * Returns false if the ticking is not running anymore.
      */
-    fn update_tickings(&self) {
-        todo!()
-    }
+    fn update(&self, delta: Time) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+
+        let keep_running = self.on_update(delta);
+        self.ticking_state().call_tick_callback();
 
+        if !keep_running {
+            self.stop_finished(true);
+        }
+
+        self.is_running()
+    }
 
     /**
      * Executed every frame while the ticking lives.
@@ -86,7 +223,7 @@ pub trait Ticking {
      * removed from the list of active tickings.
      * The end callback will automatically be called then.
      */
-    fn on_update(&self, delta: Time) {}
+    fn on_update(&self, delta: Time) -> bool;
 
     /**
      * Called when the ticking becomes active.
@@ -99,8 +236,136 @@ pub trait Ticking {
      */
     fn on_stop(&self) {}
 
+    /**
+     * Stops the ticking if it was running, then executes the end callback
+     * with the given finished flag.
+     */
     fn stop_finished(&self, finished: bool) {
-        todo!()
+        if !self.is_running() {
+            return;
+        }
+
+        self.ticking_state().running.set(false);
+        self.on_stop();
+        self.ticking_state().call_end_callback(finished);
+    }
+}
+
+thread_local! {
+    static CURRENT_SCHEDULER: RefCell<Weak<SchedulerTickings>> = RefCell::new(Weak::new());
+}
+
+struct SchedulerTickings {
+    tickings: RefCell<Vec<Box<dyn Ticking>>>,
+    last_update: Cell<Option<Time>>,
+}
+
+/// Keeps the list of running tickings and advances them once per frame.
+///
+/// The application owns one and makes it current when it is created,
+/// Ticking::start() then pushes the started tickings to it.
+pub struct TickingScheduler {
+    tickings: Rc<SchedulerTickings>,
+}
+
+impl TickingScheduler {
+    pub fn new() -> Self {
+        Self {
+            tickings: Rc::new(SchedulerTickings {
+                tickings: RefCell::new(vec![]),
+                last_update: Cell::new(None),
+            }),
+        }
+    }
+
+    /**
+     * Returns the scheduler tickings of the current thread are pushed to, if any.
+     */
+    pub fn current() -> Option<TickingScheduler> {
+        CURRENT_SCHEDULER
+            .with(|current| current.borrow().upgrade())
+            .map(|tickings| TickingScheduler { tickings })
+    }
+
+    /**
+     * Makes this scheduler the one started tickings are pushed to
+     * on the current thread.
+     */
+    pub fn make_current(&self) {
+        CURRENT_SCHEDULER.with(|current| *current.borrow_mut() = Rc::downgrade(&self.tickings));
+    }
+
+    fn schedule(&self, ticking: Box<dyn Ticking>) {
+        self.tickings.tickings.borrow_mut().push(ticking);
+    }
+
+    /**
+     * Returns true if at least one ticking is running.
+     */
+    pub fn is_ticking(&self) -> bool {
+        self.tickings
+            .tickings
+            .borrow()
+            .iter()
+            .any(|ticking| ticking.is_running())
+    }
+
+    /**
+     * Called by the main loop once per frame with the current time
+     * in microseconds. Updates every running ticking with the time
+     * elapsed since the previous frame.
+     */
+    pub fn update_tickings(&self, now: Time) {
+        let delta = match self.tickings.last_update.get() {
+            // Keep the sub-millisecond remainder for the next frame
+            Some(last_update) if now >= last_update => {
+                let delta = (now - last_update) / 1000;
+                self.tickings
+                    .last_update
+                    .set(Some(last_update + delta * 1000));
+                delta
+            }
+            // Tickings started while idle begin at the current frame,
+            // as well as every ticking if the clock went backwards
+            _ => {
+                self.tickings.last_update.set(Some(now));
+                0
+            }
+        };
+
+        self.update(delta);
+
+        if self.tickings.tickings.borrow().is_empty() {
+            self.tickings.last_update.set(None);
+        }
+    }
+
+    /**
+     * Updates every running ticking with the given delta in ms,
+     * removing the finished ones.
+     */
+    pub fn update(&self, delta: Time) {
+        // Callbacks may start new tickings, they are pushed to the emptied list
+        let tickings = std::mem::take(&mut *self.tickings.tickings.borrow_mut());
+        let mut running = Vec::with_capacity(tickings.len());
+
+        for ticking in tickings {
+            if ticking.update(delta) {
+                running.push(ticking);
+            } else {
+                ticking.ticking_state().scheduled.set(false);
+            }
+        }
+
+        let mut tickings = self.tickings.tickings.borrow_mut();
+        running.append(&mut tickings);
+        *tickings = running;
+    }
+}
+
+impl Default for TickingScheduler {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -113,14 +378,19 @@ pub trait FiniteTicking: Ticking {
      * everything in the ticking).
      * Does not start or stop it.
      */
-    fn rewind(&self) {}
+    fn rewind(&self) {
+        self.on_rewind();
+    }
 
     /**
      * Stops and resets the ticking, clearing its state
      * in the process (as opposed to rewind() that just restarts
      * the ticking from the beginning without losing the state).
      */
-    fn reset(&self) {}
+    fn reset(&self) {
+        self.stop();
+        self.on_reset();
+    }
 
     /**
      * Called when the ticking gets rewound.
@@ -131,4 +401,489 @@ pub trait FiniteTicking: Ticking {
      * Called when the ticking gets reset.
      */
     fn on_reset(&self) {}
-}
\ No newline at end of file
+}
+
+/// A ticking that runs once for the given duration in ms, then executes
+/// its end callback with finished set to true.
+///
+/// Use stop() to cancel it, the end callback then gets false.
+pub struct Timer {
+    state: Rc<TimerState>,
+}
+
+struct TimerState {
+    ticking: TickingState,
+    duration: Cell<Time>,
+    progress: Cell<Time>,
+}
+
+impl Timer {
+    pub fn new(duration: Time) -> Self {
+        Self {
+            state: Rc::new(TimerState {
+                ticking: TickingState::new(),
+                duration: Cell::new(duration),
+                progress: Cell::new(0),
+            }),
+        }
+    }
+
+    /**
+     * Sets the duration of the timer in ms. Does not rewind it.
+     */
+    pub fn set_duration(&self, duration: Time) {
+        self.state.duration.set(duration);
+    }
+
+    pub fn duration(&self) -> Time {
+        self.state.duration.get()
+    }
+
+    /**
+     * Returns the time elapsed since the timer started in ms.
+     */
+    pub fn progress(&self) -> Time {
+        self.state.progress.get()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress() >= self.duration()
+    }
+}
+
+impl Ticking for Timer {
+    fn ticking_state(&self) -> &TickingState {
+        &self.state.ticking
+    }
+
+    fn ticking_handle(&self) -> Box<dyn Ticking> {
+        Box::new(Timer {
+            state: self.state.clone(),
+        })
+    }
+
+    fn on_update(&self, delta: Time) -> bool {
+        let progress = (self.progress() + delta).min(self.duration());
+        self.state.progress.set(progress);
+
+        !self.is_finished()
+    }
+
+    fn on_start(&self) {
+        // Starting a finished timer runs it again
+        if self.is_finished() {
+            self.rewind();
+        }
+    }
+}
+
+impl FiniteTicking for Timer {
+    fn on_rewind(&self) {
+        self.state.progress.set(0);
+    }
+
+    fn on_reset(&self) {
+        self.state.progress.set(0);
+    }
+}
+
+/// A ticking that executes its callback every period in ms,
+/// until it is stopped.
+///
+/// The callback can stop the timer itself, the end callback then gets false.
+pub struct RepeatingTimer {
+    state: Rc<RepeatingTimerState>,
+}
+
+struct RepeatingTimerState {
+    ticking: TickingState,
+    period: Cell<Time>,
+    progress: Cell<Time>,
+    callback: RefCell<Option<TickingGenericCallback>>,
+}
+
+impl RepeatingTimer {
+    pub fn new(period: Time) -> Self {
+        Self {
+            state: Rc::new(RepeatingTimerState {
+                ticking: TickingState::new(),
+                period: Cell::new(period),
+                progress: Cell::new(0),
+                callback: RefCell::new(None),
+            }),
+        }
+    }
+
+    /**
+     * Sets the period of the timer in ms. A period of 0
+     * executes the callback once every frame.
+     */
+    pub fn set_period(&self, period: Time) {
+        self.state.period.set(period);
+    }
+
+    pub fn period(&self) -> Time {
+        self.state.period.get()
+    }
+
+    /**
+     * Sets the callback executed every period.
+     */
+    pub fn set_callback(&self, callback: TickingGenericCallback) {
+        *self.state.callback.borrow_mut() = Some(callback);
+    }
+
+    fn call_callback(&self) {
+        // Taken out during the call so that the callback can replace itself
+        let callback = self.state.callback.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback();
+            let mut slot = self.state.callback.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+}
+
+impl Ticking for RepeatingTimer {
+    fn ticking_state(&self) -> &TickingState {
+        &self.state.ticking
+    }
+
+    fn ticking_handle(&self) -> Box<dyn Ticking> {
+        Box::new(RepeatingTimer {
+            state: self.state.clone(),
+        })
+    }
+
+    fn on_update(&self, delta: Time) -> bool {
+        let period = self.period();
+        if period == 0 {
+            self.call_callback();
+            return self.is_running();
+        }
+
+        self.state.progress.set(self.state.progress.get() + delta);
+
+        // Catch up on every period elapsed since the last frame
+        while self.is_running() && self.state.progress.get() >= period {
+            self.state.progress.set(self.state.progress.get() - period);
+            self.call_callback();
+        }
+
+        self.is_running()
+    }
+}
+
+impl FiniteTicking for RepeatingTimer {
+    fn on_rewind(&self) {
+        self.state.progress.set(0);
+    }
+
+    fn on_reset(&self) {
+        self.state.progress.set(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Clock, FiniteTicking, ManualClock, RepeatingTimer, Ticking, TickingEndCallback,
+        TickingScheduler, Timer,
+    };
+    use crate::core::animation::{Animatable, Animating};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    /// Returns the finished flags given to the end callback, and the callback.
+    fn end_flags() -> (Rc<RefCell<Vec<bool>>>, TickingEndCallback) {
+        let flags = Rc::new(RefCell::new(vec![]));
+        let callback_flags = flags.clone();
+        let callback = Box::new(move |finished| callback_flags.borrow_mut().push(finished));
+        (flags, callback)
+    }
+
+    fn counter() -> (Rc<Cell<u32>>, Box<dyn Fn()>) {
+        let count = Rc::new(Cell::new(0));
+        let callback_count = count.clone();
+        let callback = Box::new(move || callback_count.set(callback_count.get() + 1));
+        (count, callback)
+    }
+
+    #[test]
+    fn animatable_follows_manual_clock() {
+        let clock = ManualClock::new(1_000_000);
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+
+        let animatable = Animatable::new(0.0);
+        animatable.add_step(1.0, 200);
+        animatable.start();
+
+        // The first frame only records the start time
+        scheduler.update_tickings(clock.now_usec());
+        assert_eq!(animatable.value(), 0.0);
+
+        for _ in 0..10 {
+            clock.advance(10);
+            scheduler.update_tickings(clock.now_usec());
+        }
+        assert!((animatable.value() - 0.5).abs() < f32::EPSILON);
+
+        clock.advance(100);
+        scheduler.update_tickings(clock.now_usec());
+        assert_eq!(animatable.value(), 1.0);
+        assert!(!animatable.is_running());
+    }
+
+    #[test]
+    fn timer_finishes_after_its_duration() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, callback) = end_flags();
+        let timer = Timer::new(100);
+        timer.set_end_callback(callback);
+        timer.start();
+
+        scheduler.update(60);
+        assert!(timer.is_running());
+        assert_eq!(timer.progress(), 60);
+
+        scheduler.update(60);
+        assert!(!timer.is_running());
+        assert!(timer.is_finished());
+        assert_eq!(timer.progress(), 100);
+        assert_eq!(*flags.borrow(), vec![true]);
+    }
+
+    #[test]
+    fn stopped_timer_is_not_finished() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, callback) = end_flags();
+        let timer = Timer::new(100);
+        timer.set_end_callback(callback);
+        timer.start();
+        scheduler.update(10);
+
+        timer.stop();
+        assert!(!timer.is_running());
+        assert!(!timer.is_finished());
+        // Only once
+        timer.stop();
+        scheduler.update(100);
+        assert_eq!(*flags.borrow(), vec![false]);
+    }
+
+    #[test]
+    fn finished_timer_restarts_from_the_beginning() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, callback) = end_flags();
+        let timer = Timer::new(100);
+        timer.set_end_callback(callback);
+        timer.start();
+        scheduler.update(100);
+        assert!(timer.is_finished());
+
+        timer.start();
+        assert!(timer.is_running());
+        assert_eq!(timer.progress(), 0);
+        scheduler.update(50);
+        assert_eq!(timer.progress(), 50);
+        scheduler.update(50);
+        assert_eq!(*flags.borrow(), vec![true, true]);
+    }
+
+    #[test]
+    fn rewind_keeps_running_and_reset_stops() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, callback) = end_flags();
+        let timer = Timer::new(100);
+        timer.set_end_callback(callback);
+        timer.start();
+        scheduler.update(60);
+
+        timer.rewind();
+        assert!(timer.is_running());
+        assert_eq!(timer.progress(), 0);
+        scheduler.update(60);
+        assert!(timer.is_running());
+
+        timer.reset();
+        assert!(!timer.is_running());
+        assert_eq!(timer.progress(), 0);
+        assert_eq!(*flags.borrow(), vec![false]);
+    }
+
+    #[test]
+    fn repeating_timer_catches_up_in_a_long_frame() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (count, callback) = counter();
+        let timer = RepeatingTimer::new(10);
+        timer.set_callback(callback);
+        timer.start();
+
+        scheduler.update(35);
+        assert_eq!(count.get(), 3);
+        // The remainder is kept for the next frame
+        scheduler.update(5);
+        assert_eq!(count.get(), 4);
+        assert!(timer.is_running());
+    }
+
+    #[test]
+    fn repeating_timer_without_period_runs_every_frame() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (count, callback) = counter();
+        let timer = RepeatingTimer::new(0);
+        timer.set_callback(callback);
+        timer.start();
+
+        scheduler.update(0);
+        scheduler.update(100);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn repeating_timer_stops_from_its_callback() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, end_callback) = end_flags();
+        let timer = Rc::new(RepeatingTimer::new(10));
+        let count = Rc::new(Cell::new(0));
+        let callback_timer = Rc::downgrade(&timer);
+        let callback_count = count.clone();
+        timer.set_callback(Box::new(move || {
+            callback_count.set(callback_count.get() + 1);
+            if callback_count.get() == 2 {
+                callback_timer.upgrade().unwrap().stop();
+            }
+        }));
+        timer.set_end_callback(end_callback);
+        timer.start();
+
+        // Stops catching up once stopped
+        scheduler.update(50);
+        assert_eq!(count.get(), 2);
+        assert!(!timer.is_running());
+        assert!(!scheduler.is_ticking());
+        assert_eq!(*flags.borrow(), vec![false]);
+    }
+
+    fn scheduled(scheduler: &TickingScheduler) -> usize {
+        scheduler.tickings.tickings.borrow().len()
+    }
+
+    #[test]
+    fn scheduler_removes_the_finished_tickings() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let (flags, callback) = end_flags();
+        let finishing = Timer::new(10);
+        finishing.set_end_callback(callback);
+        finishing.start();
+        let stopped = Timer::new(100);
+        stopped.start();
+        // Started twice, scheduled once
+        stopped.start();
+        assert_eq!(scheduled(&scheduler), 2);
+
+        stopped.stop();
+        scheduler.update(10);
+        assert_eq!(*flags.borrow(), vec![true]);
+        assert_eq!(scheduled(&scheduler), 0);
+        assert!(!scheduler.is_ticking());
+
+        // Scheduled again once started again
+        stopped.start();
+        assert_eq!(scheduled(&scheduler), 1);
+        assert!(scheduler.is_ticking());
+    }
+
+    #[test]
+    fn scheduler_keeps_a_ticking_restarted_by_its_end_callback() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let timer = Rc::new(Timer::new(10));
+        let ends = Rc::new(Cell::new(0));
+        let callback_timer = Rc::downgrade(&timer);
+        let callback_ends = ends.clone();
+        timer.set_end_callback(Box::new(move |finished| {
+            callback_ends.set(callback_ends.get() + 1);
+            if finished && callback_ends.get() == 1 {
+                callback_timer.upgrade().unwrap().start();
+            }
+        }));
+        timer.start();
+
+        scheduler.update(10);
+        assert!(timer.is_running());
+        assert_eq!(timer.progress(), 0);
+        assert_eq!(scheduled(&scheduler), 1);
+
+        scheduler.update(10);
+        assert_eq!(ends.get(), 2);
+        assert_eq!(scheduled(&scheduler), 0);
+    }
+
+    #[test]
+    fn tickings_started_during_an_update_run_from_the_next_one() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let started = Rc::new(Timer::new(100));
+        let trigger = Timer::new(10);
+        let callback_started = started.clone();
+        trigger.set_end_callback(Box::new(move |_| callback_started.start()));
+        trigger.start();
+
+        scheduler.update(10);
+        assert!(started.is_running());
+        assert_eq!(started.progress(), 0);
+        assert_eq!(scheduled(&scheduler), 1);
+
+        scheduler.update(10);
+        assert_eq!(started.progress(), 10);
+    }
+
+    #[test]
+    fn scheduler_carries_the_sub_millisecond_remainder() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let timer = Timer::new(100);
+        timer.start();
+
+        // The first frame only records the time
+        scheduler.update_tickings(1_000_000);
+        scheduler.update_tickings(1_000_600);
+        assert_eq!(timer.progress(), 0);
+        scheduler.update_tickings(1_001_200);
+        assert_eq!(timer.progress(), 1);
+        scheduler.update_tickings(1_001_900);
+        assert_eq!(timer.progress(), 1);
+        scheduler.update_tickings(1_002_000);
+        assert_eq!(timer.progress(), 2);
+    }
+
+    #[test]
+    fn idle_scheduler_starts_again_at_the_current_frame() {
+        let scheduler = TickingScheduler::new();
+        scheduler.make_current();
+        let timer = Timer::new(10);
+        timer.start();
+        scheduler.update_tickings(1_000_000);
+        scheduler.update_tickings(1_010_000);
+        assert!(!timer.is_running());
+
+        // Time spent idle does not count
+        timer.start();
+        scheduler.update_tickings(5_000_000);
+        assert_eq!(timer.progress(), 0);
+        scheduler.update_tickings(5_004_000);
+        assert_eq!(timer.progress(), 4);
+    }
+}
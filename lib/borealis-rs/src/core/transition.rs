@@ -0,0 +1,180 @@
+use crate::core::activity::Activity;
+use crate::core::animation::{Animatable, Animating};
+use crate::core::style::style;
+use crate::core::time::Ticking;
+use crate::core::tweening::EasingFunction;
+use crate::core::view_base::TransitionAnimation;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/**
+ * Returns the duration in ms of an activity transition.
+ */
+pub fn transition_duration(animation: TransitionAnimation) -> f32 {
+    match animation {
+        TransitionAnimation::None => 0.0,
+        TransitionAnimation::SlideLeft | TransitionAnimation::SlideRight => {
+            style("brls/animations/show_slide")
+        }
+        TransitionAnimation::Fade | TransitionAnimation::Linear => style("brls/animations/show"),
+    }
+}
+
+/**
+ * Returns the transition to pop an activity pushed with the given one:
+ * an activity sliding in from the right slides out to the right.
+ */
+pub fn reverse_transition(animation: TransitionAnimation) -> TransitionAnimation {
+    match animation {
+        TransitionAnimation::SlideLeft => TransitionAnimation::SlideRight,
+        TransitionAnimation::SlideRight => TransitionAnimation::SlideLeft,
+        animation => animation,
+    }
+}
+
+/// How an activity is drawn at some point of a transition.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransitionFrame {
+    pub alpha: f32,
+    pub offset_x: f32,
+}
+
+impl TransitionFrame {
+    pub const IDENTITY: TransitionFrame = TransitionFrame {
+        alpha: 1.0,
+        offset_x: 0.0,
+    };
+}
+
+/// The animation between two activities, when one is pushed on top of
+/// the other or popped from it.
+///
+/// Slides move both activities, in the direction of the animation.
+/// Fades only fade the top activity in or out, as well as the
+/// activity below if it is hidden by the top one once the transition is over.
+pub struct ActivityTransition {
+    animation: TransitionAnimation,
+    /// The activity above the other one, pushed or popped
+    top: Rc<RefCell<Activity>>,
+    /// The activity below, paused or resumed
+    bottom: Rc<RefCell<Activity>>,
+    pushing: bool,
+    /// Set if the bottom activity is hidden by the top one
+    fade_bottom: bool,
+    progress: Animatable,
+}
+
+impl ActivityTransition {
+    /**
+     * Starts the transition, use is_running() to know when it is over.
+     */
+    pub fn new(
+        animation: TransitionAnimation,
+        top: Rc<RefCell<Activity>>,
+        bottom: Rc<RefCell<Activity>>,
+        pushing: bool,
+        fade_bottom: bool,
+    ) -> Self {
+        let easing = match animation {
+            TransitionAnimation::Linear => EasingFunction::Linear,
+            _ => EasingFunction::QuadraticOut,
+        };
+
+        let progress = Animatable::new(0.0);
+        progress.reset_initial(0.0);
+        progress.add_step_easing(1.0, transition_duration(animation) as i32, easing);
+        progress.start();
+
+        Self {
+            animation,
+            top,
+            bottom,
+            pushing,
+            fade_bottom,
+            progress,
+        }
+    }
+
+    pub fn animation(&self) -> TransitionAnimation {
+        self.animation
+    }
+
+    pub fn top(&self) -> &Rc<RefCell<Activity>> {
+        &self.top
+    }
+
+    pub fn bottom(&self) -> &Rc<RefCell<Activity>> {
+        &self.bottom
+    }
+
+    pub fn is_pushing(&self) -> bool {
+        self.pushing
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_running()
+    }
+
+    /**
+     * Stops the animation, when another transition starts before its end.
+     */
+    pub fn stop(&self) {
+        self.progress.stop();
+    }
+
+    /**
+     * Returns how far the top activity is shown, from 0 (not shown) to 1.
+     */
+    fn shown(&self) -> f32 {
+        match self.pushing {
+            true => self.progress.value(),
+            false => 1.0 - self.progress.value(),
+        }
+    }
+
+    /**
+     * Returns how far the activities have moved in the direction
+     * of a slide, from 0 (starting position) to 1.
+     */
+    fn moved(&self) -> f32 {
+        self.progress.value()
+    }
+
+    fn direction(&self) -> f32 {
+        match self.animation {
+            TransitionAnimation::SlideLeft => -1.0,
+            TransitionAnimation::SlideRight => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn top_frame(&self, width: f32) -> TransitionFrame {
+        let shown = self.shown();
+        let offset_x = match self.pushing {
+            // Coming from the opposite side of the slide
+            true => -self.direction() * width * (1.0 - self.moved()),
+            false => self.direction() * width * self.moved(),
+        };
+
+        TransitionFrame {
+            alpha: shown,
+            offset_x,
+        }
+    }
+
+    pub fn bottom_frame(&self, width: f32) -> TransitionFrame {
+        let shown = self.shown();
+        let offset_x = match self.pushing {
+            true => self.direction() * width * self.moved(),
+            false => -self.direction() * width * (1.0 - self.moved()),
+        };
+
+        let alpha = match self.animation {
+            TransitionAnimation::SlideLeft | TransitionAnimation::SlideRight => 1.0 - shown,
+            _ if self.fade_bottom => 1.0 - shown,
+            _ => 1.0,
+        };
+
+        TransitionFrame { alpha, offset_x }
+    }
+}
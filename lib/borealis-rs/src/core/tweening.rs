@@ -1,5 +1,15 @@
+use std::f32::consts::PI;
 
-#[derive(Debug, Copy, Clone)]
+const BACK_OVERSHOOT: f32 = 1.70158;
+const BEZIER_NEWTON_ITERATIONS: usize = 8;
+const BEZIER_EPSILON: f32 = 1e-6;
+
+/// Easing functions, mapping the progress of an animation step (from 0.0 to 1.0)
+/// to the progress of its value.
+///
+/// Every function returns 0.0 for 0.0 and 1.0 for 1.0, back and elastic
+/// functions go out of that range in between.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum EasingFunction {
     Linear,
     QuadraticIn,
@@ -8,10 +18,38 @@ pub enum EasingFunction {
     CubicIn,
     CubicOut,
     CubicInOut,
+    QuarticIn,
+    QuarticOut,
+    QuarticInOut,
+    QuinticIn,
+    QuinticOut,
+    QuinticInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExponentialIn,
+    ExponentialOut,
+    ExponentialInOut,
+    CircularIn,
+    CircularOut,
+    CircularInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    /// A CSS-like cubic bezier curve going from (0, 0) to (1, 1),
+    /// with the control points (x1, y1) and (x2, y2).
+    /// x1 and x2 must be between 0.0 and 1.0.
+    CubicBezier(f32, f32, f32, f32),
 }
 
 impl EasingFunction {
-    fn apply(&self, t: f32) -> f32 {
+    pub fn apply(&self, t: f32) -> f32 {
         match *self {
             EasingFunction::Linear => t,
             EasingFunction::QuadraticIn => t * t,
@@ -36,6 +74,306 @@ impl EasingFunction {
                     0.5 * t1 * t1 * t1 + 1.0
                 }
             }
+            EasingFunction::QuarticIn => t.powi(4),
+            EasingFunction::QuarticOut => 1.0 - (1.0 - t).powi(4),
+            EasingFunction::QuarticInOut => {
+                if t < 0.5 {
+                    8.0 * t.powi(4)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(4) / 2.0
+                }
+            }
+            EasingFunction::QuinticIn => t.powi(5),
+            EasingFunction::QuinticOut => 1.0 - (1.0 - t).powi(5),
+            EasingFunction::QuinticInOut => {
+                if t < 0.5 {
+                    16.0 * t.powi(5)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(5) / 2.0
+                }
+            }
+            EasingFunction::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            EasingFunction::SineOut => (t * PI / 2.0).sin(),
+            EasingFunction::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            EasingFunction::ExponentialIn => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2f32.powf(10.0 * t - 10.0)
+                }
+            }
+            EasingFunction::ExponentialOut => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    1.0 - 2f32.powf(-10.0 * t)
+                }
+            }
+            EasingFunction::ExponentialInOut => {
+                if t <= 0.0 {
+                    0.0
+                } else if t >= 1.0 {
+                    1.0
+                } else if t < 0.5 {
+                    2f32.powf(20.0 * t - 10.0) / 2.0
+                } else {
+                    (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0
+                }
+            }
+            EasingFunction::CircularIn => 1.0 - (1.0 - t * t).max(0.0).sqrt(),
+            EasingFunction::CircularOut => (1.0 - (t - 1.0).powi(2)).max(0.0).sqrt(),
+            EasingFunction::CircularInOut => {
+                if t < 0.5 {
+                    (1.0 - (1.0 - (2.0 * t).powi(2)).max(0.0).sqrt()) / 2.0
+                } else {
+                    ((1.0 - (-2.0 * t + 2.0).powi(2)).max(0.0).sqrt() + 1.0) / 2.0
+                }
+            }
+            EasingFunction::BackIn => {
+                let c3 = BACK_OVERSHOOT + 1.0;
+                c3 * t * t * t - BACK_OVERSHOOT * t * t
+            }
+            EasingFunction::BackOut => {
+                let c3 = BACK_OVERSHOOT + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + BACK_OVERSHOOT * (t - 1.0).powi(2)
+            }
+            EasingFunction::BackInOut => {
+                let c2 = BACK_OVERSHOOT * 1.525;
+                if t < 0.5 {
+                    (2.0 * t).powi(2) * ((c2 + 1.0) * 2.0 * t - c2) / 2.0
+                } else {
+                    ((2.0 * t - 2.0).powi(2) * ((c2 + 1.0) * (t * 2.0 - 2.0) + c2) + 2.0) / 2.0
+                }
+            }
+            EasingFunction::ElasticIn => {
+                let c4 = (2.0 * PI) / 3.0;
+                if t <= 0.0 {
+                    0.0
+                } else if t >= 1.0 {
+                    1.0
+                } else {
+                    -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+                }
+            }
+            EasingFunction::ElasticOut => {
+                let c4 = (2.0 * PI) / 3.0;
+                if t <= 0.0 {
+                    0.0
+                } else if t >= 1.0 {
+                    1.0
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+                }
+            }
+            EasingFunction::ElasticInOut => {
+                let c5 = (2.0 * PI) / 4.5;
+                if t <= 0.0 {
+                    0.0
+                } else if t >= 1.0 {
+                    1.0
+                } else if t < 0.5 {
+                    -(2f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0
+                } else {
+                    (2f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0 + 1.0
+                }
+            }
+            EasingFunction::BounceIn => 1.0 - bounce_out(1.0 - t),
+            EasingFunction::BounceOut => bounce_out(t),
+            EasingFunction::BounceInOut => {
+                if t < 0.5 {
+                    (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+                } else {
+                    (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+                }
+            }
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
         }
     }
-}
\ No newline at end of file
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+/**
+ * Evaluates the y coordinate of the bezier curve at the given x,
+ * solving the curve parameter with Newton's method, falling back
+ * to bisection when the slope is too flat.
+ */
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    // Coordinate of the curve for one axis at t, and its derivative
+    let sample = |a1: f32, a2: f32, t: f32| {
+        ((1.0 - 3.0 * a2 + 3.0 * a1) * t + (3.0 * a2 - 6.0 * a1)) * t * t + 3.0 * a1 * t
+    };
+    let slope = |a1: f32, a2: f32, t: f32| {
+        3.0 * (1.0 - 3.0 * a2 + 3.0 * a1) * t * t + 2.0 * (3.0 * a2 - 6.0 * a1) * t + 3.0 * a1
+    };
+
+    let mut t = x;
+    for _ in 0..BEZIER_NEWTON_ITERATIONS {
+        let error = sample(x1, x2, t) - x;
+        if error.abs() < BEZIER_EPSILON {
+            return sample(y1, y2, t);
+        }
+
+        let derivative = slope(x1, x2, t);
+        if derivative.abs() < BEZIER_EPSILON {
+            break;
+        }
+
+        t -= error / derivative;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    while high - low > BEZIER_EPSILON {
+        let value = sample(x1, x2, t);
+        if (value - x).abs() < BEZIER_EPSILON {
+            break;
+        }
+
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+
+    sample(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EasingFunction;
+    use super::EasingFunction::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    const ALL: [EasingFunction; 32] = [
+        Linear,
+        QuadraticIn,
+        QuadraticOut,
+        QuadraticInOut,
+        CubicIn,
+        CubicOut,
+        CubicInOut,
+        QuarticIn,
+        QuarticOut,
+        QuarticInOut,
+        QuinticIn,
+        QuinticOut,
+        QuinticInOut,
+        SineIn,
+        SineOut,
+        SineInOut,
+        ExponentialIn,
+        ExponentialOut,
+        ExponentialInOut,
+        CircularIn,
+        CircularOut,
+        CircularInOut,
+        BackIn,
+        BackOut,
+        BackInOut,
+        ElasticIn,
+        ElasticOut,
+        ElasticInOut,
+        BounceIn,
+        BounceOut,
+        BounceInOut,
+        CubicBezier(0.25, 0.1, 0.25, 1.0),
+    ];
+
+    fn assert_near(easing: EasingFunction, t: f32, expected: f32) {
+        let value = easing.apply(t);
+        assert!(
+            (value - expected).abs() < EPSILON,
+            "{:?}.apply({}) = {}, expected {}",
+            easing,
+            t,
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn starts_at_zero() {
+        for easing in ALL {
+            assert_near(easing, 0.0, 0.0);
+        }
+    }
+
+    #[test]
+    fn ends_at_one() {
+        for easing in ALL {
+            assert_near(easing, 1.0, 1.0);
+        }
+    }
+
+    #[test]
+    fn in_out_is_symmetric() {
+        for easing in [
+            QuadraticInOut,
+            CubicInOut,
+            QuarticInOut,
+            QuinticInOut,
+            SineInOut,
+            ExponentialInOut,
+            CircularInOut,
+            BackInOut,
+            ElasticInOut,
+            BounceInOut,
+        ] {
+            assert_near(easing, 0.5, 0.5);
+            for t in [0.1, 0.25, 0.4] {
+                assert_near(easing, 1.0 - t, 1.0 - easing.apply(t));
+            }
+        }
+    }
+
+    #[test]
+    fn back_overshoots() {
+        assert!(BackIn.apply(0.2) < 0.0);
+        assert!(BackOut.apply(0.8) > 1.0);
+    }
+
+    #[test]
+    fn cubic_bezier() {
+        // Straight line
+        for t in [0.1, 0.5, 0.9] {
+            assert_near(CubicBezier(0.0, 0.0, 1.0, 1.0), t, t);
+        }
+
+        // CSS "ease"
+        assert_near(CubicBezier(0.25, 0.1, 0.25, 1.0), 0.5, 0.8024);
+
+        // Flat slope at the start
+        assert_near(CubicBezier(1.0, 0.0, 0.0, 1.0), 0.5, 0.5);
+
+        // Out of range progress is clamped
+        assert_near(CubicBezier(0.25, 0.1, 0.25, 1.0), -1.0, 0.0);
+        assert_near(CubicBezier(0.25, 0.1, 0.25, 1.0), 2.0, 1.0);
+    }
+}
@@ -1,24 +1,33 @@
 use std::cell::RefCell;
 use crate::core::theme;
-use crate::core::view_box::{BoxEnum, BoxTrait, BoxView};
+use crate::core::view_box::{register_box_attributes, BoxEnum, BoxTrait};
 use nanovg::Context;
 use nanovg_sys::{
     nvgBeginFrame, nvgBeginPath, nvgEndFrame, nvgFill, nvgFillColor, nvgRect, NVGcolor,
 };
 use std::cmp::PartialEq;
 use std::ffi::c_float;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use yoga_sys::{YGNodeFree, YGNodeNew, YGNodeRef};
-use crate::core::animation::Animatable;
+use crate::core::actions::{Action, ActionIdentifier};
+use crate::core::animation::{Animatable, Animating};
+use crate::core::attribute::XMLAttributes;
 use crate::core::audio::Sound;
+use crate::core::frame_context::FrameContext;
 use crate::core::geometry::Point;
+use crate::core::gesture::{GestureRecognizer, SharedGestureRecognizer};
+use crate::core::input::ControllerButton;
+use crate::core::style::style;
+use crate::core::time::{FiniteTicking, Ticking};
+use crate::core::tweening::EasingFunction;
 use crate::core::view_drawer::{ViewDrawer, ViewTrait};
-use crate::core::view_layout::ViewLayout;
-use crate::core::view_style::ViewStyle;
-use crate::views::image::Image;
-use crate::views::label::Label;
+use crate::core::view_layout::{register_layout_attributes, ViewLayout};
+use crate::core::view_style::{register_style_attributes, ViewStyle};
+use crate::views::applet_frame::register_applet_frame_attributes;
+use crate::views::image::{register_image_attributes, Image};
+use crate::views::label::{register_label_attributes, Label};
 use crate::views::progress_spinner::ProgressSpinner;
-use crate::views::rectangle::Rectangle;
+use crate::views::rectangle::{register_rectangle_attributes, Rectangle};
 
 // common ViewData
 pub struct ViewData {
@@ -49,6 +58,9 @@ pub struct ViewData {
     pub line_bottom: f32,
     pub line_right: f32,
     pub highlight_alpha: Animatable,
+    pub highlight_shake: Animatable,
+    pub highlight_shake_direction: FocusDirection,
+    pub highlight_shake_amplitude: f32,
     pub highlight_corner_radius: f32,
     pub highlight_padding: f32,
     pub hide_click_animation: bool,
@@ -59,14 +71,17 @@ pub struct ViewData {
     pub collapse_state: Animatable,
     pub clips_to_bounds: bool,
     pub wireframe_enabled: bool,
-    pub parent: Option<Rc<RefCell<BoxEnum>>>,
-    pub view: Option<Rc<RefCell<View>>>,
+    pub actions: Vec<Action>,
+    pub next_action_identifier: ActionIdentifier,
+    pub gesture_recognizers: Vec<SharedGestureRecognizer>,
+    pub parent: Option<Weak<RefCell<View>>>,
+    pub view: Option<Weak<RefCell<View>>>,
 }
 
 impl Default for ViewData {
     fn default() -> Self {
         Self {
-            id: "fake_id".to_string(),
+            id: String::new(),
             background: ViewBackground::VerticalLinear,
             background_color: theme::theme("brls/background"),
             background_start_color: theme::theme("brls/background"),
@@ -80,7 +95,7 @@ impl Default for ViewData {
             detached: false,
             detached_origin: Default::default(),
             focusable: false,
-            focused: true,
+            focused: false,
             focus_sound: Sound::SoundNone,
             shadow_type: ShadowType::Generic,
             show_shadow: true,
@@ -93,6 +108,9 @@ impl Default for ViewData {
             line_bottom: 4.1,
             line_right: 4.1,
             highlight_alpha: Animatable::new(0.0),
+            highlight_shake: Animatable::new(0.0),
+            highlight_shake_direction: FocusDirection::Right,
+            highlight_shake_amplitude: 0.0,
             highlight_corner_radius: 0.0,
             highlight_padding: 0.0,
             hide_click_animation: false,
@@ -103,6 +121,9 @@ impl Default for ViewData {
             collapse_state: Animatable::new(1.0),
             clips_to_bounds: false,
             wireframe_enabled: true,
+            actions: vec![],
+            next_action_identifier: 1,
+            gesture_recognizers: vec![],
             parent: None,
             view: None,
         }
@@ -156,34 +177,199 @@ pub trait ViewBase {
     fn data(&self) -> &ViewData;
     fn data_mut(&mut self) -> &mut ViewData;
 
+    /**
+     * Called when the view gains the focus.
+     */
     fn on_focus_gained(&mut self) {
         self.data_mut().focused = true;
-        todo!()
+
+        // Fade the highlight in
+        let highlight_alpha = &self.data().highlight_alpha;
+        highlight_alpha.reset();
+        highlight_alpha.add_step_easing(
+            1.0,
+            style("brls/animations/highlight") as i32,
+            EasingFunction::QuadraticOut,
+        );
+        highlight_alpha.start();
     }
 
+    /**
+     * Called when the view loses the focus.
+     */
     fn on_focus_lost(&mut self) {
         self.data_mut().focused = false;
-        todo!()
+
+        // Fade the highlight out
+        let highlight_alpha = &self.data().highlight_alpha;
+        highlight_alpha.reset();
+        highlight_alpha.add_step_easing(
+            0.0,
+            style("brls/animations/highlight") as i32,
+            EasingFunction::QuadraticOut,
+        );
+        highlight_alpha.start();
+    }
+
+    fn is_focused(&self) -> bool {
+        self.data().focused
+    }
+
+    /**
+     * Returns the view to focus when this view is
+     * asked for focus, or None if it cannot take it.
+     *
+     * Boxes look for a focusable view among their children.
+     */
+    fn default_focus(&self) -> Option<Rc<RefCell<View>>> {
+        match self.is_focusable() {
+            true => self.view(),
+            false => None,
+        }
+    }
+
+    /**
+     * Returns the view the focus should go to when leaving the given
+     * direct child in the given direction, or None to let the parent decide.
+     */
+    fn next_focus(
+        &self,
+        _direction: FocusDirection,
+        _current_child: &Rc<RefCell<View>>,
+    ) -> Option<Rc<RefCell<View>>> {
+        None
+    }
+
+    /**
+     * Registers an action triggered by the given button while the focus is on
+     * this view or one of its children. The listener is called with this view
+     * and returns true if it consumed the button press, false to let
+     * the parents handle it.
+     *
+     * Registering an action on a button that already has one replaces it.
+     * Hidden actions are not displayed in the hints.
+     *
+     * Returns the identifier of the action, to unregister it later.
+     */
+    fn register_action(
+        &mut self,
+        hint_text: &str,
+        button: ControllerButton,
+        listener: Box<dyn Fn(Rc<RefCell<View>>) -> bool>,
+        hidden: bool,
+    ) -> ActionIdentifier {
+        let data = self.data_mut();
+        // Never reused, an old identifier cannot unregister a newer action
+        let identifier = data.next_action_identifier;
+        data.next_action_identifier += 1;
+        let actions = &mut data.actions;
+        let action = Action {
+            button,
+            identifier,
+            hint_text: hint_text.into(),
+            available: true,
+            hidden,
+            listener: Rc::from(listener),
+        };
+
+        match actions.iter_mut().find(|action| action.button == button) {
+            Some(existing) => *existing = action,
+            None => actions.push(action),
+        }
+
+        identifier
+    }
+
+    fn unregister_action(&mut self, identifier: ActionIdentifier) {
+        self.data_mut()
+            .actions
+            .retain(|action| action.identifier != identifier);
+    }
+
+    /**
+     * Changes the hint text of the action registered on the given button.
+     */
+    fn update_action_hint(&mut self, button: ControllerButton, hint_text: &str) {
+        if let Some(action) = self.action_mut(button) {
+            action.hint_text = hint_text.into();
+        }
+    }
+
+    /**
+     * Enables or disables the action registered on the given button.
+     * Unavailable actions are not triggered and their hint is displayed as disabled.
+     */
+    fn set_action_available(&mut self, button: ControllerButton, available: bool) {
+        if let Some(action) = self.action_mut(button) {
+            action.available = available;
+        }
+    }
+
+    fn set_actions_available(&mut self, available: bool) {
+        for action in &mut self.data_mut().actions {
+            action.available = available;
+        }
+    }
+
+    fn actions(&self) -> &Vec<Action> {
+        &self.data().actions
+    }
+
+    /**
+     * Returns the action registered on the given button, if any.
+     */
+    fn action(&self, button: ControllerButton) -> Option<&Action> {
+        self.data()
+            .actions
+            .iter()
+            .find(|action| action.button == button)
+    }
+
+    fn action_mut(&mut self, button: ControllerButton) -> Option<&mut Action> {
+        self.data_mut()
+            .actions
+            .iter_mut()
+            .find(|action| action.button == button)
+    }
+
+    /**
+     * Adds a gesture recognizer, fed with the touches starting
+     * on this view or one of its children.
+     */
+    fn add_gesture_recognizer(&mut self, recognizer: Box<dyn GestureRecognizer>) {
+        self.data_mut()
+            .gesture_recognizers
+            .push(Rc::new(RefCell::new(recognizer)));
+    }
+
+    fn gesture_recognizers(&self) -> &Vec<SharedGestureRecognizer> {
+        &self.data().gesture_recognizers
     }
 
     fn animate_hint(&self) -> bool {
         false
     }
 
-    fn set_background(&self, background: ViewBackground) {
-        todo!()
+    fn set_background(&mut self, background: ViewBackground) {
+        self.data_mut().background = background;
     }
 
     /**
-     * Returns the "nearest" view with the corresponding id, or nullptr if none has
+     * Returns the "nearest" view with the corresponding id, or None if none has
      * been found. "Nearest" means the closest in the vicinity
      * of this view. The siblings are searched as well as its children.
      *
      * Research is done by traversing the tree upwards, starting from this view.
-     * The current algorithm is very inefficient.
+     * Each parent skips the child the research comes from, already searched.
      */
-    fn get_nearest_view(&self) -> Rc<RefCell<View>> {
-        todo!()
+    fn get_nearest_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        if self.data().id == id {
+            if let Some(view) = self.view() {
+                return Some(view);
+            }
+        }
+
+        get_nearest_view_in_parents(self.view(), self.parent(), id)
     }
 
     /**
@@ -211,9 +397,9 @@ pub trait ViewBase {
      * Removes view from it's parent
      */
     fn remove_from_super_view(&self, free: bool) {
-        if let Some(parent) = &self.data().parent {
-            if let Some(self_ref) = self.view() {
-                parent.borrow_mut().remove_view(self_ref, free);
+        if let (Some(parent), Some(self_ref)) = (self.parent(), self.view()) {
+            if let View::Box(parent) = &mut *parent.borrow_mut() {
+                parent.remove_view(self_ref, free);
             }
         }
     }
@@ -277,23 +463,23 @@ pub trait ViewBase {
     }
 
     fn has_parent(&self) -> bool {
-        self.data().parent.is_some()
+        self.parent().is_some()
     }
 
-    fn set_parent(&mut self, parent: Option<Rc<RefCell<BoxEnum>>>) {
-        self.data_mut().parent = parent;
+    fn set_parent(&mut self, parent: Option<Rc<RefCell<View>>>) {
+        self.data_mut().parent = parent.as_ref().map(Rc::downgrade);
     }
 
-    fn parent(&self) -> &Option<Rc<RefCell<BoxEnum>>> {
-        &self.data().parent
+    fn parent(&self) -> Option<Rc<RefCell<View>>> {
+        self.data().parent.as_ref().and_then(Weak::upgrade)
     }
 
     /// ref to self
     fn view(&self) -> Option<Rc<RefCell<View>>> {
-        self.data().view.clone()
+        self.data().view.as_ref().and_then(Weak::upgrade)
     }
 
-    fn set_view(&mut self, self_ref: Rc<RefCell<View>>) {
+    fn set_view(&mut self, self_ref: Weak<RefCell<View>>) {
         self.data_mut().view = Some(self_ref);
     }
 
@@ -321,6 +507,31 @@ pub fn ntz(value: f32) -> f32 {
     value
 }
 
+/**
+ * Searches the given parent then its own parents for the view with the given id.
+ * Each of them skips its child the research comes from, which was already searched.
+ */
+fn get_nearest_view_in_parents(
+    mut child: Option<Rc<RefCell<View>>>,
+    mut parent: Option<Rc<RefCell<View>>>,
+    id: &str,
+) -> Option<Rc<RefCell<View>>> {
+    while let Some(view) = parent {
+        let found = match &*view.borrow() {
+            View::Box(parent) => parent.get_view_skipping(id, child.as_ref()),
+            parent => parent.get_view(id),
+        };
+        if found.is_some() {
+            return found;
+        }
+
+        parent = view.borrow().parent();
+        child = Some(view);
+    }
+
+    None
+}
+
 pub enum View {
     Box(BoxEnum),
     Image(Image),
@@ -329,10 +540,96 @@ pub enum View {
     Rectangle(Rectangle),
 }
 
+impl View {
+    /// Moves the view into a shared reference, keeping a weak ref to itself
+    /// so that children added later can point back to it as their parent.
+    pub fn into_rc(self) -> Rc<RefCell<View>> {
+        let view = Rc::new_cyclic(|self_ref| {
+            let mut view = self;
+            view.set_view(self_ref.clone());
+            RefCell::new(view)
+        });
+
+        // Children added before could not point back to their parent yet
+        if let View::Box(parent) = &*view.borrow() {
+            for child in parent.children() {
+                child.borrow_mut().set_parent(Some(view.clone()));
+            }
+        }
+
+        view
+    }
+}
+
 impl ViewBase for View {
+    fn on_focus_gained(&mut self) {
+        match self {
+            View::Box(view) => BoxTrait::on_focus_gained(view),
+            View::Image(view) => ViewBase::on_focus_gained(view),
+            View::Label(view) => ViewBase::on_focus_gained(view),
+            View::ProgressSpinner(view) => ViewBase::on_focus_gained(view),
+            View::Rectangle(view) => ViewBase::on_focus_gained(view),
+        }
+    }
+
+    fn on_focus_lost(&mut self) {
+        match self {
+            View::Box(view) => BoxTrait::on_focus_lost(view),
+            View::Image(view) => ViewBase::on_focus_lost(view),
+            View::Label(view) => ViewBase::on_focus_lost(view),
+            View::ProgressSpinner(view) => ViewBase::on_focus_lost(view),
+            View::Rectangle(view) => ViewBase::on_focus_lost(view),
+        }
+    }
+
+    fn on_parent_focus_gained(&self, focused_view: Rc<RefCell<View>>) {
+        if let View::Box(view) = self {
+            BoxTrait::on_parent_focus_gained(view, focused_view);
+        }
+    }
+
+    fn on_parent_focus_lost(&self, focused_view: Rc<RefCell<View>>) {
+        if let View::Box(view) = self {
+            BoxTrait::on_parent_focus_lost(view, focused_view);
+        }
+    }
+
+    fn default_focus(&self) -> Option<Rc<RefCell<View>>> {
+        match self {
+            View::Box(view) => BoxTrait::default_focus(view),
+            _ if self.is_focusable() => self.view(),
+            _ => None,
+        }
+    }
+
+    fn next_focus(
+        &self,
+        direction: FocusDirection,
+        current_child: &Rc<RefCell<View>>,
+    ) -> Option<Rc<RefCell<View>>> {
+        match self {
+            View::Box(view) => BoxTrait::next_focus(view, direction, current_child),
+            _ => None,
+        }
+    }
+
+    fn get_nearest_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self {
+            // Without any data, it has neither an id nor a parent
+            View::ProgressSpinner(_) => None,
+            // First try to find a child with that id, then try with the parents
+            _ => self
+                .get_view(id)
+                .or_else(|| get_nearest_view_in_parents(self.view(), self.parent(), id)),
+        }
+    }
+
     fn data(&self) -> &ViewData {
         match self {
             View::Box(v) => v.data(),
+            View::Image(v) => v.data(),
+            View::Label(v) => v.data(),
+            View::Rectangle(v) => v.data(),
             _ => todo!(),
         }
     }
@@ -340,16 +637,116 @@ impl ViewBase for View {
     fn data_mut(&mut self) -> &mut ViewData {
         match self {
             View::Box(v) => v.data_mut(),
+            View::Image(v) => v.data_mut(),
+            View::Label(v) => v.data_mut(),
+            View::Rectangle(v) => v.data_mut(),
             _ => todo!(),
         }
     }
 }
 
+impl View {
+    /**
+     * Returns the XML attributes accepted by this view: the ones
+     * common to every view, then the ones specific to its type.
+     */
+    pub fn xml_attributes(&self) -> XMLAttributes {
+        let mut attributes = XMLAttributes::new();
+
+        register_common_attributes(&mut attributes);
+        register_layout_attributes(&mut attributes);
+        register_style_attributes(&mut attributes);
+
+        match self {
+            View::Box(view) => {
+                register_box_attributes(&mut attributes);
+                if let BoxEnum::AppletFrame(_) = view {
+                    register_applet_frame_attributes(&mut attributes);
+                }
+            }
+            View::Image(_) => register_image_attributes(&mut attributes),
+            View::Label(_) => register_label_attributes(&mut attributes),
+            View::Rectangle(_) => register_rectangle_attributes(&mut attributes),
+            View::ProgressSpinner(_) => {}
+        }
+
+        attributes
+    }
+}
+
+/// Attributes backed by the ViewBase setters.
+fn register_common_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_bool_attribute("focusable", |view, value| view.set_focusable(value));
+    attributes.register_bool_attribute("wireframe", |view, value| {
+        view.data_mut().wireframe_enabled = value
+    });
+    attributes.register_enum_attribute(
+        "background",
+        &[
+            ("none", ViewBackground::None),
+            ("sidebar", ViewBackground::SideBar),
+            ("backdrop", ViewBackground::BackDrop),
+            ("shapeColor", ViewBackground::ShapeColor),
+            ("verticalLinear", ViewBackground::VerticalLinear),
+        ],
+        |view, value| view.set_background(value),
+    );
+}
+
 impl ViewTrait for View {}
 
-impl ViewDrawer for View {}
+impl ViewDrawer for View {
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, width: f32, height: f32) {
+        match self {
+            View::Box(view) => view.draw(ctx, x, y, width, height),
+            View::Image(view) => view.draw(ctx, x, y, width, height),
+            View::Label(view) => view.draw(ctx, x, y, width, height),
+            View::ProgressSpinner(view) => view.draw(ctx, x, y, width, height),
+            View::Rectangle(view) => view.draw(ctx, x, y, width, height),
+        }
+    }
 
-impl ViewLayout for View {}
+    fn will_appear(&self, reset_state: bool) {
+        match self {
+            View::Box(view) => view.will_appear(reset_state),
+            View::Image(view) => view.will_appear(reset_state),
+            View::Label(view) => view.will_appear(reset_state),
+            View::ProgressSpinner(view) => view.will_appear(reset_state),
+            View::Rectangle(view) => view.will_appear(reset_state),
+        }
+    }
+
+    fn will_disappear(&self, reset_state: bool) {
+        match self {
+            View::Box(view) => view.will_disappear(reset_state),
+            View::Image(view) => view.will_disappear(reset_state),
+            View::Label(view) => view.will_disappear(reset_state),
+            View::ProgressSpinner(view) => view.will_disappear(reset_state),
+            View::Rectangle(view) => view.will_disappear(reset_state),
+        }
+    }
+}
+
+impl ViewLayout for View {
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self {
+            View::Box(view) => view.get_view(id),
+            View::ProgressSpinner(_) => None,
+            _ if self.data().id == id => self.view(),
+            _ => None,
+        }
+    }
+
+    fn hit_test(&self, point: &Point) -> Option<Rc<RefCell<View>>> {
+        match self {
+            View::Box(view) => BoxTrait::hit_test(view, point),
+            View::Image(view) => ViewLayout::hit_test(view, point),
+            View::Label(view) => ViewLayout::hit_test(view, point),
+            View::ProgressSpinner(view) => ViewLayout::hit_test(view, point),
+            View::Rectangle(view) => ViewLayout::hit_test(view, point),
+        }
+    }
+}
 
 impl ViewStyle for View {}
 
@@ -409,3 +806,84 @@ pub enum ShadowType {
     Generic,
     Custom,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{View, ViewBase};
+    use crate::core::view_box::BoxTrait;
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use crate::core::view_layout::ViewLayout;
+    use crate::views::progress_spinner::ProgressSpinner;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn parse(xml: &str) -> Rc<RefCell<View>> {
+        XMLParser::new(&XMLViewRegistry::new(), "<test>".into(), xml)
+            .parse()
+            .unwrap()
+    }
+
+    /// Two boxes with a label each sharing the same id, "title"
+    fn tree() -> Rc<RefCell<View>> {
+        let root = parse(
+            r#"<brls:Box id="root">
+                <brls:Box id="left">
+                    <brls:Label id="title"/>
+                    <brls:Label id="a"/>
+                </brls:Box>
+            </brls:Box>"#,
+        );
+        // Ids are unique in a file, the other one comes from another file
+        let right = parse(
+            r#"<brls:Box id="right">
+                <brls:Label id="title"/>
+                <brls:Label id="b"/>
+            </brls:Box>"#,
+        );
+        if let View::Box(root) = &mut *root.borrow_mut() {
+            root.add_view(right);
+        }
+        root
+    }
+
+    fn is(found: Option<Rc<RefCell<View>>>, view: &Rc<RefCell<View>>) -> bool {
+        found.map_or(false, |found| Rc::ptr_eq(&found, view))
+    }
+
+    #[test]
+    fn get_view_searches_the_view_and_its_descendants_only() {
+        let root = tree();
+        let left = root.borrow().get_view("left").unwrap();
+        let b = root.borrow().get_view("b").unwrap();
+
+        assert!(is(root.borrow().get_view("root"), &root));
+        assert_eq!(b.borrow().data().id, "b");
+        assert!(root.borrow().get_view("missing").is_none());
+        assert!(left.borrow().get_view("b").is_none());
+    }
+
+    #[test]
+    fn get_nearest_view_prefers_the_closest_view() {
+        let root = tree();
+        let left = root.borrow().get_view("left").unwrap();
+        let right = root.borrow().get_view("right").unwrap();
+        let a = left.borrow().get_view("a").unwrap();
+        let b = right.borrow().get_view("b").unwrap();
+        let left_title = left.borrow().get_view("title").unwrap();
+        let right_title = right.borrow().get_view("title").unwrap();
+
+        assert!(is(a.borrow().get_nearest_view("a"), &a));
+        assert!(is(a.borrow().get_nearest_view("title"), &left_title));
+        assert!(is(b.borrow().get_nearest_view("title"), &right_title));
+        assert!(is(a.borrow().get_nearest_view("b"), &b));
+        assert!(is(a.borrow().get_nearest_view("root"), &root));
+        assert!(a.borrow().get_nearest_view("missing").is_none());
+    }
+
+    #[test]
+    fn progress_spinner_has_no_id() {
+        let spinner = View::ProgressSpinner(ProgressSpinner);
+        assert!(spinner.get_view("spinner").is_none());
+        assert!(spinner.get_nearest_view("spinner").is_none());
+    }
+}
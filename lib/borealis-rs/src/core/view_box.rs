@@ -1,9 +1,22 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use yoga_sys::{YGNodeGetChildCount, YGNodeInsertChild, YGNodeRemoveChild, YGNodeStyleGetPadding, YGNodeStyleSetPadding};
+use yoga_sys::{YGNodeInsertChild, YGNodeRemoveChild, YGNodeStyleGetPadding, YGNodeStyleSetAlignItems, YGNodeStyleSetFlexDirection, YGNodeStyleSetJustifyContent, YGNodeStyleSetPadding};
+use yoga_sys::YGAlign::{
+    YGAlignAuto, YGAlignBaseline, YGAlignCenter, YGAlignFlexEnd, YGAlignFlexStart,
+    YGAlignSpaceAround, YGAlignSpaceBetween, YGAlignStretch,
+};
 use yoga_sys::YGEdge::{YGEdgeBottom, YGEdgeLeft, YGEdgeRight, YGEdgeTop};
-use crate::core::view_base::{View, ViewBase, ViewData};
+use yoga_sys::YGFlexDirection::{YGFlexDirectionColumn, YGFlexDirectionRow};
+use yoga_sys::YGJustify::{
+    YGJustifyCenter, YGJustifyFlexEnd, YGJustifyFlexStart, YGJustifySpaceAround,
+    YGJustifySpaceBetween, YGJustifySpaceEvenly,
+};
+use crate::core::animation::Animating;
+use crate::core::geometry::Point;
+use crate::core::view_base::{FocusDirection, View, ViewBase, ViewData, Visibility};
+use crate::core::attribute::XMLAttributes;
+use crate::core::frame_context::FrameContext;
 use crate::core::view_drawer::{ViewDrawer, ViewTrait};
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
@@ -23,6 +36,7 @@ use crate::views::scrolling_frame::ScrollingFrame;
 use crate::views::slider::Slider;
 use crate::views::tab_frame::TabFrame;
 
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum JustifyContent {
     FlexStart,
     Center,
@@ -32,6 +46,7 @@ pub enum JustifyContent {
     SpaceEvenly,
 }
 
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum AlignItems {
     Auto,
     FlexStart,
@@ -43,11 +58,13 @@ pub enum AlignItems {
     SpaceAround,
 }
 
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Axis {
     Row,
     Column,
 }
 
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Direction {
     Inherit,
     LeftToRight,
@@ -55,13 +72,13 @@ pub enum Direction {
 }
 
 pub struct BoxView {
-    view_data: ViewData,
+    box_view_data: BoxViewData,
 }
 
 impl BoxView {
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         let s = Self {
-            view_data: ViewData::default(),
+            box_view_data: BoxViewData::default(),
         };
         s.set_width(width);
         s.set_height(height);
@@ -69,23 +86,64 @@ impl BoxView {
         s.set_position_left(y);
         s
     }
+
+    /**
+     * Creates a box with automatic dimensions laying out
+     * its children along the given axis.
+     */
+    pub fn with_axis(axis: Axis) -> Self {
+        let mut s = Self {
+            box_view_data: BoxViewData::default(),
+        };
+        s.set_axis(axis);
+        s
+    }
 }
 
 impl ViewBase for BoxView {
     fn data(&self) -> &ViewData {
-        &self.view_data
+        &self.box_view_data.view_data
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        &mut self.view_data
+        &mut self.box_view_data.view_data
+    }
+}
+
+impl BoxTrait for BoxView {
+    fn box_view_data(&self) -> &BoxViewData {
+        &self.box_view_data
+    }
+
+    fn box_view_data_mut(&mut self) -> &mut BoxViewData {
+        &mut self.box_view_data
     }
 }
 
 impl ViewStyle for BoxView {}
 
-impl ViewLayout for BoxView {}
+impl ViewLayout for BoxView {
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self.data().id == id {
+            true => self.view(),
+            false => self.get_child_view(id),
+        }
+    }
+}
+
+impl ViewDrawer for BoxView {
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, width: f32, height: f32) {
+        self.frame_children(ctx);
+    }
+
+    fn will_appear(&self, reset_state: bool) {
+        self.children_will_appear(reset_state);
+    }
 
-impl ViewDrawer for BoxView {}
+    fn will_disappear(&self, reset_state: bool) {
+        self.children_will_disappear(reset_state);
+    }
+}
 
 pub enum BoxEnum {
     Box(BoxView),
@@ -109,9 +167,46 @@ pub enum BoxEnum {
 
 impl ViewTrait for BoxEnum {}
 
-impl ViewDrawer for BoxEnum {}
+impl ViewDrawer for BoxEnum {
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, width: f32, height: f32) {
+        match self {
+            BoxEnum::Box(v) => v.draw(ctx, x, y, width, height),
+            BoxEnum::AppletFrame(v) => v.draw(ctx, x, y, width, height),
+            BoxEnum::Hint(v) => v.draw(ctx, x, y, width, height),
+            BoxEnum::Hints(v) => v.draw(ctx, x, y, width, height),
+            _ => {}
+        }
+    }
 
-impl ViewLayout for BoxEnum {}
+    fn will_appear(&self, reset_state: bool) {
+        match self {
+            BoxEnum::Box(v) => v.will_appear(reset_state),
+            BoxEnum::AppletFrame(v) => v.will_appear(reset_state),
+            BoxEnum::Hint(v) => v.will_appear(reset_state),
+            BoxEnum::Hints(v) => v.will_appear(reset_state),
+            _ => {}
+        }
+    }
+
+    fn will_disappear(&self, reset_state: bool) {
+        match self {
+            BoxEnum::Box(v) => v.will_disappear(reset_state),
+            BoxEnum::AppletFrame(v) => v.will_disappear(reset_state),
+            BoxEnum::Hint(v) => v.will_disappear(reset_state),
+            BoxEnum::Hints(v) => v.will_disappear(reset_state),
+            _ => {}
+        }
+    }
+}
+
+impl ViewLayout for BoxEnum {
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self.data().id == id {
+            true => self.view(),
+            false => self.get_child_view(id),
+        }
+    }
+}
 
 impl ViewStyle for BoxEnum {}
 
@@ -120,6 +215,8 @@ impl ViewBase for BoxEnum {
         match self {
             BoxEnum::Box(v) => v.data(),
             BoxEnum::AppletFrame(v) => v.data(),
+            BoxEnum::Hint(v) => v.data(),
+            BoxEnum::Hints(v) => v.data(),
             _ => todo!(),
         }
     }
@@ -128,6 +225,8 @@ impl ViewBase for BoxEnum {
         match self {
             BoxEnum::Box(v) => v.data_mut(),
             BoxEnum::AppletFrame(v) => v.data_mut(),
+            BoxEnum::Hint(v) => v.data_mut(),
+            BoxEnum::Hints(v) => v.data_mut(),
             _ => todo!(),
         }
     }
@@ -141,12 +240,62 @@ pub struct BoxViewData {
 
     default_focused_index: usize,
     last_focused_view: Option<Rc<RefCell<View>>>,
-    forwarded_attributes: HashMap<String, (String, Rc<RefCell<RefCell<View>>>)>,
-    box_view: Option<Rc<RefCell<BoxEnum>>>,
+    forwarded_attributes: HashMap<String, (String, Rc<RefCell<View>>)>,
+}
+
+impl BoxViewData {
+    pub fn view_data(&self) -> &ViewData {
+        &self.view_data
+    }
+
+    pub fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.view_data
+    }
+}
+
+impl Default for BoxViewData {
+    fn default() -> Self {
+        Self {
+            view_data: ViewData::default(),
+            axis: Axis::Row,
+            children: vec![],
+            default_focused_index: 0,
+            last_focused_view: None,
+            forwarded_attributes: HashMap::new(),
+        }
+    }
 }
 
 impl BoxTrait for BoxEnum {
+    fn box_view_data(&self) -> &BoxViewData {
+        match self {
+            BoxEnum::Box(v) => v.box_view_data(),
+            BoxEnum::AppletFrame(v) => v.box_view_data(),
+            BoxEnum::Hint(v) => v.box_view_data(),
+            BoxEnum::Hints(v) => v.box_view_data(),
+            _ => todo!(),
+        }
+    }
 
+    fn box_view_data_mut(&mut self) -> &mut BoxViewData {
+        match self {
+            BoxEnum::Box(v) => v.box_view_data_mut(),
+            BoxEnum::AppletFrame(v) => v.box_view_data_mut(),
+            BoxEnum::Hint(v) => v.box_view_data_mut(),
+            BoxEnum::Hints(v) => v.box_view_data_mut(),
+            _ => todo!(),
+        }
+    }
+
+    fn add_view(&mut self, view: Rc<RefCell<View>>) {
+        match self {
+            BoxEnum::AppletFrame(v) => v.add_view(view),
+            _ => {
+                let position = self.children().len();
+                self.add_view_position(view, position);
+            }
+        }
+    }
 }
 
 // Generic FlexBox layout
@@ -165,10 +314,8 @@ pub trait BoxTrait: ViewDrawer {
      * Returns the position the view was added at.
      */
     fn add_view(&mut self, view: Rc<RefCell<View>>) {
-        let position = unsafe {
-            YGNodeGetChildCount(self.data().yg_node)
-        };
-        self.add_view_position(view, position as usize);
+        let position = self.box_view_data().children.len();
+        self.add_view_position(view, position);
     }
 
     /**
@@ -190,7 +337,7 @@ pub trait BoxTrait: ViewDrawer {
         }
 
         /// todo: userdata
-        view.borrow_mut().set_parent(self.box_view());
+        view.borrow_mut().set_parent(self.view());
 
         // Layout and events
         self.invalidate();
@@ -216,6 +363,7 @@ pub trait BoxTrait: ViewDrawer {
                 }
             }
 
+            view.borrow_mut().set_parent(None);
             view.borrow().will_disappear(true);
             if free {
                 view.borrow().free_view();
@@ -235,6 +383,7 @@ pub trait BoxTrait: ViewDrawer {
                 YGNodeRemoveChild(yg_node, view.borrow().data().yg_node);
             }
 
+            view.borrow_mut().set_parent(None);
             view.borrow().will_disappear(true);
             if free {
                 view.borrow().free_view();
@@ -244,6 +393,53 @@ pub trait BoxTrait: ViewDrawer {
         self.invalidate();
     }
 
+    /**
+     * Returns the child under the given point, the last drawn first,
+     * or this Box if no child is under the point.
+     */
+    fn hit_test(&self, point: &Point) -> Option<Rc<RefCell<View>>> {
+        if self.data().visibility != Visibility::Visible || self.data().alpha.value() == 0.0 {
+            return None;
+        }
+
+        if !self.rect().contains(point) {
+            return None;
+        }
+
+        self.children()
+            .iter()
+            .rev()
+            .find_map(|child| child.try_borrow().ok()?.hit_test(point))
+            .or_else(|| self.view())
+    }
+
+    /**
+     * Draws the children of this Box, in order.
+     */
+    fn frame_children(&self, ctx: &FrameContext) {
+        for child in self.children() {
+            child.borrow().frame(ctx);
+        }
+    }
+
+    /**
+     * Forwards will_appear() to the children, for boxes to call from their own.
+     */
+    fn children_will_appear(&self, reset_state: bool) {
+        for child in self.children() {
+            child.borrow().will_appear(reset_state);
+        }
+    }
+
+    /**
+     * Forwards will_disappear() to the children, for boxes to call from their own.
+     */
+    fn children_will_disappear(&self, reset_state: bool) {
+        for child in self.children() {
+            child.borrow().will_disappear(reset_state);
+        }
+    }
+
     fn on_focus_gained(&mut self) {
         ViewBase::on_focus_gained(self);
 
@@ -260,19 +456,19 @@ pub trait BoxTrait: ViewDrawer {
         }
     }
 
-    fn on_parent_focus_gained(&mut self, focused_view: Rc<RefCell<View>>) {
-        ViewBase::on_parent_focus_gained(self, focused_view);
+    fn on_parent_focus_gained(&self, focused_view: Rc<RefCell<View>>) {
+        ViewBase::on_parent_focus_gained(self, focused_view.clone());
 
         for child in &self.box_view_data().children {
-            child.borrow().on_parent_focus_gained(self.view().as_ref().unwrap().clone())
+            child.borrow().on_parent_focus_gained(focused_view.clone())
         }
     }
 
-    fn on_parent_focus_lost(&mut self, focused_view: Rc<RefCell<View>>) {
-        ViewBase::on_parent_focus_lost(self, focused_view);
+    fn on_parent_focus_lost(&self, focused_view: Rc<RefCell<View>>) {
+        ViewBase::on_parent_focus_lost(self, focused_view.clone());
 
         for child in &self.box_view_data().children {
-            child.borrow().on_parent_focus_lost(self.view().as_ref().unwrap().clone())
+            child.borrow().on_parent_focus_lost(focused_view.clone())
         }
     }
 
@@ -342,18 +538,311 @@ pub trait BoxTrait: ViewDrawer {
         }
     }
 
-    fn default_focus(&self) -> Option<Rc<RefCell<View>>> {
-        None
+    /**
+     * Sets the children alignment along the Box axis.
+     *
+     * Default is FLEX_START.
+     */
+    fn set_justify_content(&self, justify: JustifyContent) {
+        unsafe {
+            match justify {
+                JustifyContent::FlexStart => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifyFlexStart)
+                }
+                JustifyContent::Center => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifyCenter)
+                }
+                JustifyContent::FlexEnd => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifyFlexEnd)
+                }
+                JustifyContent::SpaceBetween => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifySpaceBetween)
+                }
+                JustifyContent::SpaceAround => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifySpaceAround)
+                }
+                JustifyContent::SpaceEvenly => {
+                    YGNodeStyleSetJustifyContent(self.data().yg_node, YGJustifySpaceEvenly)
+                }
+            }
+        }
+        self.invalidate();
+    }
+
+    /**
+     * Sets the children alignment along the Box invert axis.
+     *
+     * Default is AUTO.
+     */
+    fn set_align_items(&self, align_items: AlignItems) {
+        unsafe {
+            match align_items {
+                AlignItems::Auto => YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignAuto),
+                AlignItems::FlexStart => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignFlexStart)
+                }
+                AlignItems::Center => YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignCenter),
+                AlignItems::FlexEnd => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignFlexEnd)
+                }
+                AlignItems::Stretch => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignStretch)
+                }
+                AlignItems::Baseline => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignBaseline)
+                }
+                AlignItems::SpaceBetween => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignSpaceBetween)
+                }
+                AlignItems::SpaceAround => {
+                    YGNodeStyleSetAlignItems(self.data().yg_node, YGAlignSpaceAround)
+                }
+            }
+        }
+        self.invalidate();
+    }
+
+    /**
+     * Sets the direction of the Box, aka the place
+     * where views are added (rows or columns).
+     */
+    fn set_axis(&mut self, axis: Axis) {
+        unsafe {
+            match axis {
+                Axis::Row => YGNodeStyleSetFlexDirection(self.data().yg_node, YGFlexDirectionRow),
+                Axis::Column => {
+                    YGNodeStyleSetFlexDirection(self.data().yg_node, YGFlexDirectionColumn)
+                }
+            }
+        }
+        self.box_view_data_mut().axis = axis;
+        self.invalidate();
     }
 
-    fn box_view(&self) -> Option<Rc<RefCell<BoxEnum>>> {
-        self.box_view_data().box_view.clone()
+    fn axis(&self) -> Axis {
+        self.box_view_data().axis
     }
 
-    fn set_box_view(&mut self, box_view: Option<Rc<RefCell<BoxEnum>>>) {
-        self.box_view_data_mut().box_view = box_view;
+    /**
+     * Returns the children of this Box.
+     */
+    fn children(&self) -> &Vec<Rc<RefCell<View>>> {
+        &self.box_view_data().children
     }
-}
 
+    /**
+     * Returns the view with the corresponding id among the descendants
+     * of this Box, or None if it hasn't been found.
+     *
+     * Children currently mutably borrowed are skipped, they cannot be searched.
+     */
+    fn get_child_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        self.children()
+            .iter()
+            .find_map(|child| child.try_borrow().ok()?.get_view(id))
+    }
+
+    /**
+     * Returns the view with the corresponding id in this Box or its
+     * descendants, without searching the given child and its own descendants.
+     */
+    fn get_view_skipping(
+        &self,
+        id: &str,
+        skipped: Option<&Rc<RefCell<View>>>,
+    ) -> Option<Rc<RefCell<View>>> {
+        if self.data().id == id {
+            return self.view();
+        }
+
+        self.children()
+            .iter()
+            .filter(|child| skipped.map_or(true, |skipped| !Rc::ptr_eq(child, skipped)))
+            .find_map(|child| child.try_borrow().ok()?.get_view(id))
+    }
+
+    /**
+     * Forwards the XML attribute "name" of this Box to the attribute
+     * "target_name" of the target view, usually one of its children.
+     *
+     * Allows reusable layouts to expose the attributes of their children,
+     * for instance a "title" attribute setting the text of a label.
+     */
+    fn forward_xml_attribute(&mut self, name: &str, target: Rc<RefCell<View>>, target_name: &str) {
+        self.box_view_data_mut()
+            .forwarded_attributes
+            .insert(name.into(), (target_name.into(), target));
+    }
+
+    /**
+     * Returns the target view and attribute name the given
+     * attribute is forwarded to, if any.
+     */
+    fn forwarded_xml_attribute(&self, name: &str) -> Option<(String, Rc<RefCell<View>>)> {
+        self.box_view_data().forwarded_attributes.get(name).cloned()
+    }
+
+    /**
+     * Sets the index of the child to focus first when the focus
+     * enters this Box for the first time.
+     *
+     * Default is 0.
+     */
+    fn set_default_focused_index(&mut self, index: usize) {
+        self.box_view_data_mut().default_focused_index = index;
+    }
+
+    fn default_focused_index(&self) -> usize {
+        self.box_view_data().default_focused_index
+    }
+
+    /**
+     * Returns the direct child that contained the focus the last time
+     * the focus was inside this Box.
+     */
+    fn last_focused_view(&self) -> Option<Rc<RefCell<View>>> {
+        self.box_view_data().last_focused_view.clone()
+    }
+
+    fn set_last_focused_view(&mut self, view: Option<Rc<RefCell<View>>>) {
+        self.box_view_data_mut().last_focused_view = view;
+    }
+
+    /**
+     * Returns the view to focus when the focus enters this Box: the Box
+     * itself if focusable, then the child that was last focused, then
+     * the default focused child, then the first child that can take the focus.
+     */
+    fn default_focus(&self) -> Option<Rc<RefCell<View>>> {
+        // Focus ourself first
+        if self.is_focusable() {
+            return self.view();
+        }
+
+        let children = self.children();
+
+        // Then go back to where the focus was
+        if let Some(last_focused_view) = self.last_focused_view() {
+            if children.iter().any(|child| Rc::ptr_eq(child, &last_focused_view)) {
+                if let Some(focus) = last_focused_view.borrow().default_focus() {
+                    return Some(focus);
+                }
+            }
+        }
+
+        // Then try the default focused child
+        if let Some(child) = children.get(self.default_focused_index()) {
+            if let Some(focus) = child.borrow().default_focus() {
+                return Some(focus);
+            }
+        }
+
+        // Fallback to the first focusable child
+        children.iter().find_map(|child| child.borrow().default_focus())
+    }
 
+    /**
+     * Returns the next view to focus among the siblings of the given child
+     * in the given direction. Directions that don't follow the Box axis
+     * return None, leaving the decision to the parent.
+     */
+    fn next_focus(
+        &self,
+        direction: FocusDirection,
+        current_child: &Rc<RefCell<View>>,
+    ) -> Option<Rc<RefCell<View>>> {
+        let offset: isize = match (self.axis(), direction) {
+            (Axis::Row, FocusDirection::Left) | (Axis::Column, FocusDirection::Up) => -1,
+            (Axis::Row, FocusDirection::Right) | (Axis::Column, FocusDirection::Down) => 1,
+            _ => return None,
+        };
 
+        let children = self.children();
+        let mut index = children
+            .iter()
+            .position(|child| Rc::ptr_eq(child, current_child))? as isize
+            + offset;
+
+        while index >= 0 && (index as usize) < children.len() {
+            if let Some(focus) = children[index as usize].borrow().default_focus() {
+                return Some(focus);
+            }
+            index += offset;
+        }
+
+        None
+    }
+}
+
+/// Attributes only accepted by boxes.
+pub fn register_box_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_enum_attribute(
+        "axis",
+        &[("row", Axis::Row), ("column", Axis::Column)],
+        |view, value| {
+            if let View::Box(view) = view {
+                view.set_axis(value);
+            }
+        },
+    );
+    attributes.register_enum_attribute(
+        "justifyContent",
+        &[
+            ("flexStart", JustifyContent::FlexStart),
+            ("center", JustifyContent::Center),
+            ("flexEnd", JustifyContent::FlexEnd),
+            ("spaceBetween", JustifyContent::SpaceBetween),
+            ("spaceAround", JustifyContent::SpaceAround),
+            ("spaceEvenly", JustifyContent::SpaceEvenly),
+        ],
+        |view, value| {
+            if let View::Box(view) = view {
+                view.set_justify_content(value);
+            }
+        },
+    );
+    attributes.register_enum_attribute(
+        "alignItems",
+        &[
+            ("auto", AlignItems::Auto),
+            ("flexStart", AlignItems::FlexStart),
+            ("center", AlignItems::Center),
+            ("flexEnd", AlignItems::FlexEnd),
+            ("stretch", AlignItems::Stretch),
+            ("baseline", AlignItems::Baseline),
+            ("spaceBetween", AlignItems::SpaceBetween),
+            ("spaceAround", AlignItems::SpaceAround),
+        ],
+        |view, value| {
+            if let View::Box(view) = view {
+                view.set_align_items(value);
+            }
+        },
+    );
+
+    attributes.register_float_attribute("padding", |view, value| {
+        if let View::Box(view) = view {
+            view.set_padding(value);
+        }
+    });
+    attributes.register_float_attribute("paddingTop", |view, value| {
+        if let View::Box(view) = view {
+            view.set_padding_top(value);
+        }
+    });
+    attributes.register_float_attribute("paddingRight", |view, value| {
+        if let View::Box(view) = view {
+            view.set_padding_right(value);
+        }
+    });
+    attributes.register_float_attribute("paddingBottom", |view, value| {
+        if let View::Box(view) = view {
+            view.set_padding_bottom(value);
+        }
+    });
+    attributes.register_float_attribute("paddingLeft", |view, value| {
+        if let View::Box(view) = view {
+            view.set_padding_left(value);
+        }
+    });
+}
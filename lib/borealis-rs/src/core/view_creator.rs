@@ -1,15 +1,86 @@
-use crate::core::activity::Activity;
+use crate::core::resource::{resolve_res, resources_path, ResourceReference};
 use crate::core::view_base::View;
-use crate::core::view_box::{BoxEnum, BoxView};
-use quick_xml::events::Event;
+use crate::core::view_box::{Axis, BoxEnum, BoxTrait, BoxView};
+use crate::views::applet_frame::AppletFrame;
+use crate::views::hint::Hints;
+use crate::views::image::Image;
+use crate::views::label::Label;
+use crate::views::rectangle::Rectangle;
+use anyhow::Context;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use std::cell::RefCell;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-const CUSTOM_RESOURCES_PATH: &str = "resources";
+pub type XMLViewCreator = Box<dyn Fn() -> View>;
+
+/// Lookup table of the XML tags an application knows how to inflate.
+/// The built-in brls:* views are registered from the start.
+pub struct XMLViewRegistry {
+    creators: HashMap<String, XMLViewCreator>,
+}
+
+impl XMLViewRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            creators: HashMap::new(),
+        };
+
+        registry.insert(
+            "brls:Box",
+            Box::new(|| View::Box(BoxEnum::Box(BoxView::with_axis(Axis::Row)))),
+        );
+        registry.insert(
+            "brls:AppletFrame",
+            Box::new(|| View::Box(BoxEnum::AppletFrame(AppletFrame::new()))),
+        );
+        registry.insert("brls:Hints", Box::new(|| View::Box(BoxEnum::Hints(Hints::new()))));
+        registry.insert("brls:Image", Box::new(|| View::Image(Image::new())));
+        registry.insert("brls:Label", Box::new(|| View::Label(Label::new(""))));
+        registry.insert("brls:Rectangle", Box::new(|| View::Rectangle(Rectangle::new())));
+
+        registry
+    }
+
+    fn insert(&mut self, name: &str, creator: XMLViewCreator) {
+        self.creators.insert(name.into(), creator);
+    }
+
+    /**
+     * Registers a view creator for the given XML tag.
+     * Fails if the tag is already registered.
+     */
+    pub fn register(&mut self, name: &str, creator: XMLViewCreator) -> anyhow::Result<()> {
+        if self.is_registered(name) {
+            anyhow::bail!("XML view \"{}\" is already registered", name);
+        }
+
+        self.insert(name, creator);
+        Ok(())
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.creators.contains_key(name)
+    }
+
+    /**
+     * Instantiates the view registered for the given XML tag,
+     * or returns None if the tag is unknown.
+     */
+    pub fn create(&self, name: &str) -> Option<View> {
+        self.creators.get(name).map(|creator| creator())
+    }
+}
+
+impl Default for XMLViewRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 pub trait ViewCreator {
 
@@ -22,8 +93,12 @@ pub trait ViewCreator {
      * Use registerXMLView() to add your own views to the table so that
      * you can use them in your own XML files.
      */
-    fn create_from_xml_string(&self, xml: String) -> Rc<RefCell<View>> {
-        todo!()
+    fn create_from_xml_string(
+        &self,
+        registry: &XMLViewRegistry,
+        xml: String,
+    ) -> anyhow::Result<Rc<RefCell<View>>> {
+        Ok(XMLParser::new(registry, PathBuf::from("<string>"), &xml).parse()?)
     }
 
     /**
@@ -35,41 +110,36 @@ pub trait ViewCreator {
      * Use registerXMLView() to add your own views to the table so that
      * you can use them in your own XML files.
      */
-    fn create_from_xml_file(&self, name: PathBuf) -> Rc<RefCell<View>> {
+    fn create_from_xml_file(
+        &self,
+        registry: &XMLViewRegistry,
+        name: PathBuf,
+    ) -> anyhow::Result<Rc<RefCell<View>>> {
+        self.create_from_xml_file_with_files(registry, name).0
+    }
+
+    /**
+     * Creates a view from the given XML file path like create_from_xml_file(),
+     * also returning every file inflated for it: the file itself and the ones
+     * it includes. The files are returned even if the view could not be
+     * inflated, so that they can be watched until the error is fixed.
+     */
+    fn create_from_xml_file_with_files(
+        &self,
+        registry: &XMLViewRegistry,
+        name: PathBuf,
+    ) -> (anyhow::Result<Rc<RefCell<View>>>, Vec<PathBuf>) {
         trace!("create_from_xml_file: {:?}", name);
 
-        // let file = File::open(name).expect("Unable to open file");
-        // let file = BufReader::new(file);
-        //
-        // let mut reader = Reader::from_reader(file);
-        // reader.config_mut().trim_text(true);
-        //
-        // let mut buf = Vec::new();
-        // loop {
-        //     buf.clear();
-        //     match reader.read_event_into(&mut buf).unwrap() {
-        //         Event::Eof => break,
-        //         Event::Start(ref e) => {
-        //             trace!("{:?} start", e.name());
-        //
-        //             for attr in e.attributes() {
-        //                 if let Ok(attr) = attr {
-        //                     trace!(
-        //                         "              {:?}={:?}",
-        //                         attr.key,
-        //                         String::from_utf8(Vec::from(attr.value))
-        //                     )
-        //                 }
-        //             }
-        //         }
-        //         Event::End(ref e) => {
-        //             trace!("{:?} end", e.name());
-        //         }
-        //         _ => {}
-        //     }
-        // }
-
-        Rc::new(RefCell::new(View::Box(BoxEnum::Box(BoxView::new(0.0, 0.0, 0.0, 0.0)))))
+        let xml = match std::fs::read_to_string(&name)
+            .with_context(|| format!("unable to read XML file {:?}", name))
+        {
+            Ok(xml) => xml,
+            Err(e) => return (Err(e), vec![name]),
+        };
+
+        let (view, files) = XMLParser::new(registry, name, &xml).parse_with_files();
+        (view.map_err(anyhow::Error::from), files)
     }
 
     /**
@@ -81,10 +151,559 @@ pub trait ViewCreator {
      * Use registerXMLView() to add your own views to the table so that
      * you can use them in your own XML files.
      */
-    fn create_from_xml_resource(&self, name: PathBuf) -> Rc<RefCell<View>> {
-        let path_buf: PathBuf = PathBuf::from(CUSTOM_RESOURCES_PATH);
-        self.create_from_xml_file(path_buf.join("xml").join(name))
+    fn create_from_xml_resource(
+        &self,
+        registry: &XMLViewRegistry,
+        name: PathBuf,
+    ) -> anyhow::Result<Rc<RefCell<View>>> {
+        self.create_from_xml_file(registry, resources_path().join("xml").join(name))
+    }
+}
+
+/// A problem found while inflating an XML file, with its position in the file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct XMLDiagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for XMLDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+    }
+}
+
+/// Every problem found while inflating an XML file.
+#[derive(Debug)]
+pub struct XMLError {
+    pub diagnostics: Vec<XMLDiagnostic>,
+}
+
+impl fmt::Display for XMLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to inflate XML view")?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for XMLError {}
+
+/// Walks an XML document and builds the matching view tree.
+///
+/// Parsing does not stop at the first error: every problem is collected
+/// so that all of them can be reported at once.
+///
+/// `<brls:View xml="@res/xml/..."/>` inflates another XML file in place,
+/// its other attributes are applied to the root of the included file.
+/// A Box can expose the attributes of its children with
+/// `forward:name="child_id/attribute"` (or `forward:name="child_id"` to keep the name).
+pub struct XMLParser<'a> {
+    registry: &'a XMLViewRegistry,
+    file: PathBuf,
+    source: &'a str,
+    diagnostics: Vec<XMLDiagnostic>,
+    // Files being inflated, from the outermost one, to detect include cycles
+    includes: Vec<PathBuf>,
+    // Every file inflated so far, this one and the ones it includes
+    files: Vec<PathBuf>,
+    // Line of each id defined in this file
+    ids: HashMap<String, usize>,
+}
+
+/// An element waiting for its closing tag.
+struct XMLElement {
+    // None if the element could not be created, its children are still checked
+    view: Option<Rc<RefCell<View>>>,
+    forwards: Vec<XMLForward>,
+}
+
+/// A `forward:name="target_id/target_name"` attribute, resolved
+/// once the children of the element are known.
+struct XMLForward {
+    line: usize,
+    name: String,
+    target_id: String,
+    target_name: String,
+}
+
+impl<'a> XMLParser<'a> {
+    pub fn new(registry: &'a XMLViewRegistry, file: PathBuf, source: &'a str) -> Self {
+        Self {
+            registry,
+            includes: vec![file.clone()],
+            files: vec![file.clone()],
+            file,
+            source,
+            diagnostics: vec![],
+            ids: HashMap::new(),
+        }
+    }
+
+    /**
+     * Builds the view tree, returning its root view.
+     */
+    pub fn parse(self) -> Result<Rc<RefCell<View>>, XMLError> {
+        self.parse_with_files().0
+    }
+
+    /**
+     * Builds the view tree like parse(), also returning every file
+     * inflated for it: this one first, then the ones it includes.
+     */
+    pub fn parse_with_files(mut self) -> (Result<Rc<RefCell<View>>, XMLError>, Vec<PathBuf>) {
+        let root = self.build();
+        let files = std::mem::take(&mut self.files);
+
+        let result = match root {
+            Some(root) if self.diagnostics.is_empty() => Ok(root),
+            None if self.diagnostics.is_empty() => {
+                self.error(1, "no root element".into());
+                Err(XMLError {
+                    diagnostics: self.diagnostics,
+                })
+            }
+            _ => Err(XMLError {
+                diagnostics: self.diagnostics,
+            }),
+        };
+        (result, files)
+    }
+
+    /**
+     * Walks the document, returning the root view if there is one.
+     */
+    fn build(&mut self) -> Option<Rc<RefCell<View>>> {
+        let mut reader = Reader::from_str(self.source);
+        reader.config_mut().trim_text(true);
+
+        let mut stack: Vec<XMLElement> = vec![];
+        let mut root = None;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(ref e)) => {
+                    let element = self.create_element(e);
+                    stack.push(element);
+                }
+                Ok(Event::Empty(ref e)) => {
+                    let line = self.line_of(e.name().as_ref());
+                    let element = self.create_element(e);
+                    let view = self.finish(element);
+                    self.attach(&stack, &mut root, view, line);
+                }
+                Ok(Event::End(ref e)) => {
+                    let line = self.line_of(e.name().as_ref());
+                    let view = match stack.pop() {
+                        Some(element) => self.finish(element),
+                        None => None,
+                    };
+                    self.attach(&stack, &mut root, view, line);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let line = self.line_at(reader.error_position() as usize);
+                    self.error(line, format!("malformed XML: {}", e));
+                    break;
+                }
+            }
+        }
+
+        root
+    }
+
+    fn create_element(&mut self, element: &BytesStart) -> XMLElement {
+        let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+        let line = self.line_of(element.name().as_ref());
+
+        let mut attributes = vec![];
+        for attribute in element.attributes() {
+            match attribute {
+                Ok(attribute) => {
+                    let key = String::from_utf8_lossy(attribute.key.as_ref()).to_string();
+                    let line = self.line_of(attribute.key.as_ref());
+                    match attribute.unescape_value() {
+                        Ok(value) => attributes.push((line, key, value.to_string())),
+                        Err(e) => self.error(line, format!("malformed attribute: {}", e)),
+                    }
+                }
+                Err(e) => self.error(line, format!("malformed attribute: {}", e)),
+            }
+        }
+
+        let view = if name == "brls:View" {
+            let xml = attributes
+                .iter()
+                .position(|(_, key, _)| key == "xml")
+                .map(|index| attributes.remove(index));
+            match xml {
+                Some((line, _, xml)) => self.include(&xml, line),
+                None => {
+                    self.error(line, "<brls:View> requires an \"xml\" attribute".into());
+                    None
+                }
+            }
+        } else {
+            match self.registry.create(&name) {
+                Some(view) => Some(view.into_rc()),
+                None => {
+                    self.error(line, format!("unknown XML tag \"{}\"", name));
+                    None
+                }
+            }
+        };
+
+        let mut forwards = vec![];
+        for (line, key, value) in attributes {
+            if let Some(forwarded) = key.strip_prefix("forward:") {
+                let (target_id, target_name) = value.split_once('/').unwrap_or((&value, forwarded));
+                forwards.push(XMLForward {
+                    line,
+                    name: forwarded.into(),
+                    target_id: target_id.into(),
+                    target_name: target_name.into(),
+                });
+            } else if let Some(view) = &view {
+                if key == "id" {
+                    self.check_id(&value, line);
+                }
+                if let Err(message) = apply_xml_attribute(view, &key, &value) {
+                    self.error(line, format!("<{}>: {}", name, message));
+                }
+            }
+        }
+
+        XMLElement { view, forwards }
+    }
+
+    fn check_id(&mut self, id: &str, line: usize) {
+        match self.ids.get(id) {
+            Some(first) => {
+                let message = format!("duplicate id \"{}\", first defined at line {}", id, first);
+                self.error(line, message);
+            }
+            None => {
+                self.ids.insert(id.into(), line);
+            }
+        }
+    }
+
+    /**
+     * Inflates the XML file referenced by a <brls:View xml="..."/> element.
+     */
+    fn include(&mut self, xml: &str, line: usize) -> Option<Rc<RefCell<View>>> {
+        let path = match ResourceReference::parse(xml) {
+            Some(ResourceReference::Res(res)) => match resolve_res(xml) {
+                Ok(path) => path,
+                Err(unresolved) => {
+                    // Watched all the same, creating the file fixes the error
+                    self.add_file(resources_path().join(res));
+                    self.error(line, unresolved.to_string());
+                    return None;
+                }
+            },
+            Some(_) => {
+                self.error(line, format!("cannot include \"{}\", expected a @res/ reference", xml));
+                return None;
+            }
+            None => PathBuf::from(xml),
+        };
+
+        if self.includes.iter().any(|file| same_file(file, &path)) {
+            let cycle = self
+                .includes
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            self.error(line, format!("include cycle: {}", cycle));
+            return None;
+        }
+
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                // Watched all the same, creating the file fixes the error
+                self.add_file(path.clone());
+                self.error(line, format!("unable to read \"{}\": {}", path.display(), e));
+                return None;
+            }
+        };
+
+        let mut includes = self.includes.clone();
+        includes.push(path.clone());
+
+        let parser = XMLParser {
+            registry: self.registry,
+            file: path.clone(),
+            source: &source,
+            diagnostics: vec![],
+            includes,
+            files: vec![path.clone()],
+            ids: HashMap::new(),
+        };
+
+        let (view, files) = parser.parse_with_files();
+        for file in files {
+            self.add_file(file);
+        }
+
+        match view {
+            Ok(view) => Some(view),
+            Err(error) => {
+                self.error(line, format!("unable to include \"{}\"", path.display()));
+                self.diagnostics.extend(error.diagnostics);
+                None
+            }
+        }
+    }
+
+    /**
+     * Called once all the children of an element have been added,
+     * sets up the attributes the element forwards to them.
+     */
+    fn finish(&mut self, element: XMLElement) -> Option<Rc<RefCell<View>>> {
+        let view = element.view?;
+
+        for forward in element.forwards {
+            let target = match &*view.borrow() {
+                View::Box(view) => view.get_child_view(&forward.target_id),
+                _ => None,
+            };
+            match (&mut *view.borrow_mut(), target) {
+                (View::Box(view), Some(target)) => {
+                    view.forward_xml_attribute(&forward.name, target, &forward.target_name)
+                }
+                (View::Box(_), None) => self.error(
+                    forward.line,
+                    format!(
+                        "cannot forward attribute \"{}\": no child with id \"{}\"",
+                        forward.name, forward.target_id
+                    ),
+                ),
+                _ => self.error(forward.line, "only boxes can forward attributes".into()),
+            }
+        }
+
+        Some(view)
+    }
+
+    /**
+     * Adds a freshly built view to the element on top of the stack,
+     * or makes it the root view if the stack is empty.
+     */
+    fn attach(
+        &mut self,
+        stack: &[XMLElement],
+        root: &mut Option<Rc<RefCell<View>>>,
+        view: Option<Rc<RefCell<View>>>,
+        line: usize,
+    ) {
+        let Some(view) = view else {
+            return;
+        };
+
+        match stack.last() {
+            // Parent could not be created, error has already been reported
+            Some(XMLElement { view: None, .. }) => {}
+            Some(XMLElement {
+                view: Some(parent), ..
+            }) => match &mut *parent.borrow_mut() {
+                View::Box(parent) => parent.add_view(view),
+                _ => self.error(line, "only boxes can have children".into()),
+            },
+            None => {
+                if root.is_some() {
+                    self.error(line, "XML files can only have one root element".into());
+                } else {
+                    *root = Some(view);
+                }
+            }
+        }
+    }
+
+    fn add_file(&mut self, file: PathBuf) {
+        if !self.files.contains(&file) {
+            self.files.push(file);
+        }
+    }
+
+    fn error(&mut self, line: usize, message: String) {
+        self.diagnostics.push(XMLDiagnostic {
+            file: self.file.clone(),
+            line,
+            message,
+        });
+    }
+
+    /**
+     * Returns the line of a slice borrowed from the source.
+     */
+    fn line_of(&self, bytes: &[u8]) -> usize {
+        let start = self.source.as_ptr() as usize;
+        let offset = (bytes.as_ptr() as usize).saturating_sub(start);
+        self.line_at(offset)
+    }
+
+    fn line_at(&self, offset: usize) -> usize {
+        let offset = offset.min(self.source.len());
+        self.source.as_bytes()[..offset]
+            .iter()
+            .filter(|&&c| c == b'\n')
+            .count()
+            + 1
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Applies an XML attribute to the view, following forwarded
+/// attributes to their target view.
+///
+/// No borrow of the view is kept while the target is modified,
+/// since changing its layout invalidates its parents.
+pub fn apply_xml_attribute(view: &Rc<RefCell<View>>, name: &str, value: &str) -> Result<(), String> {
+    let forwarded = match &*view.borrow() {
+        View::Box(view) => view.forwarded_xml_attribute(name),
+        _ => None,
+    };
+
+    if let Some((target_name, target)) = forwarded {
+        return apply_xml_attribute(&target, &target_name, value);
     }
+
+    let attributes = view.borrow().xml_attributes();
+    attributes.apply(&mut view.borrow_mut(), name, value)
 }
 
-impl ViewCreator for Activity {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::temp_dir::TempDir;
+    use crate::core::view_layout::ViewLayout;
+    use crate::views::label::LabelTrait;
+
+    struct TestCreator;
+
+    impl ViewCreator for TestCreator {}
+
+    #[test]
+    fn registering_a_tag_twice_fails() {
+        let mut registry = XMLViewRegistry::default();
+        let creator = || -> XMLViewCreator { Box::new(|| View::Label(Label::new("custom"))) };
+        registry.register("app:Custom", creator()).unwrap();
+        assert!(registry.is_registered("app:Custom"));
+
+        let error = registry.register("app:Custom", creator()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "XML view \"app:Custom\" is already registered"
+        );
+        assert!(registry.register("brls:Label", creator()).is_err());
+    }
+
+    #[test]
+    fn unknown_tag_creates_nothing() {
+        let registry = XMLViewRegistry::default();
+        assert!(registry.create("app:Unknown").is_none());
+        assert!(matches!(
+            registry.create("brls:Label"),
+            Some(View::Label(_))
+        ));
+    }
+
+    #[test]
+    fn include_cycle_is_an_error() {
+        let dir = TempDir::with_files(
+            "view-creator-cycle",
+            &[
+                (
+                    "a.xml",
+                    "<brls:Box>\n    <brls:View xml=\"$DIR/b.xml\"/>\n</brls:Box>",
+                ),
+                (
+                    "b.xml",
+                    "<brls:Box>\n\n    <brls:View xml=\"$DIR/a.xml\"/>\n</brls:Box>",
+                ),
+            ],
+        );
+        let (a, b) = (dir.join("a.xml"), dir.join("b.xml"));
+
+        let registry = XMLViewRegistry::new();
+        let error = TestCreator
+            .create_from_xml_file(&registry, a.clone())
+            .err()
+            .unwrap();
+        let error = error.downcast::<XMLError>().unwrap();
+
+        let messages: Vec<String> = error.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!("{}:2: unable to include \"{}\"", a.display(), b.display()),
+                format!(
+                    "{}:3: include cycle: {} -> {} -> {}",
+                    b.display(),
+                    a.display(),
+                    b.display(),
+                    a.display()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn forwarded_attribute_reaches_the_child() {
+        let dir = TempDir::with_files(
+            "view-creator-forward",
+            &[
+                (
+                    "cell.xml",
+                    r#"<brls:Box forward:title="label/text">
+                        <brls:Label id="label"/>
+                    </brls:Box>"#,
+                ),
+                (
+                    "main.xml",
+                    r#"<brls:Box>
+                        <brls:View xml="$DIR/cell.xml" title="Hello"/>
+                    </brls:Box>"#,
+                ),
+            ],
+        );
+        let main = dir.join("main.xml");
+
+        let registry = XMLViewRegistry::new();
+        let (root, files) = TestCreator.create_from_xml_file_with_files(&registry, main.clone());
+        let label = root.unwrap().borrow().get_view("label").unwrap();
+        match &*label.borrow() {
+            View::Label(label) => assert_eq!(label.full_text(), "Hello"),
+            _ => panic!("not a label"),
+        }
+        assert_eq!(files, vec![main, dir.join("cell.xml")]);
+    }
+
+    #[test]
+    fn forward_to_a_missing_child_is_an_error() {
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box forward:title="label/text"/>"#;
+        let error = TestCreator
+            .create_from_xml_string(&registry, xml.into())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.downcast::<XMLError>().unwrap().diagnostics[0].message,
+            "cannot forward attribute \"title\": no child with id \"label\""
+        );
+    }
+}
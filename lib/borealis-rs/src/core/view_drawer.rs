@@ -1,17 +1,20 @@
+use crate::core::animation::{highlight_animation, shake_animation, Animating};
 use crate::core::application::{get_input_type, InputType};
 use crate::core::frame_context::FrameContext;
 use crate::core::geometry::Rect;
 use crate::core::style::style;
 use crate::core::theme::{theme, transparent_color};
+use crate::core::time::Ticking;
+use crate::core::tweening::EasingFunction;
 use crate::core::view_base;
-use crate::core::view_base::{ShadowType, TransitionAnimation, View, ViewBackground, ViewBase, Visibility};
+use crate::core::view_base::{FocusDirection, ShadowType, TransitionAnimation, View, ViewBackground, ViewBase, Visibility};
 use crate::core::view_layout::ViewLayout;
-use nanovg::Context;
 use nanovg_sys::{
     nvgBeginPath, nvgBoxGradient, nvgClosePath, nvgFill, nvgFillColor, nvgFillPaint,
     nvgIntersectScissor, nvgLineTo, nvgLinearGradient, nvgMoveTo, nvgPathWinding, nvgRGB, nvgRGBA,
-    nvgRect, nvgRestore, nvgRoundedRect, nvgRoundedRectVarying, nvgSave, nvgStroke, nvgStrokeColor,
-    nvgStrokeWidth, NVGcolor, NVGsolidity,
+    nvgRGBAf, nvgRadialGradient, nvgRect, nvgResetScissor, nvgRestore, nvgRoundedRect,
+    nvgRoundedRectVarying, nvgSave, nvgStroke, nvgStrokeColor, nvgStrokePaint, nvgStrokeWidth,
+    NVGcolor, NVGsolidity,
 };
 use std::ffi::{c_float, c_uchar};
 use yoga_sys::YGEdge::{YGEdgeBottom, YGEdgeLeft, YGEdgeRight, YGEdgeTop};
@@ -44,7 +47,7 @@ pub trait ViewDrawer: ViewLayout {
         let width = rect.width();
         let height = rect.height();
 
-        if self.data().alpha.current_value > 0.0 {
+        if self.data().alpha.value() > 0.0 {
             // Draw background
             self.draw_background(ctx, &rect);
 
@@ -63,7 +66,7 @@ pub trait ViewDrawer: ViewLayout {
             self.draw_line(ctx, &rect);
 
             // Draw highlight background
-            if self.data().highlight_alpha.current_value > 0.0
+            if self.data().highlight_alpha.value() > 0.0
                 && !self.data().hide_highlight_background
                 && !self.data().hide_highlight
             {
@@ -71,12 +74,12 @@ pub trait ViewDrawer: ViewLayout {
             }
 
             // Draw click animation
-            if self.data().click_alpha.current_value > 0.0 {
+            if self.data().click_alpha.value() > 0.0 {
                 self.draw_click_animation(ctx, &rect);
             }
 
             // Collapse clipping
-            if self.data().collapse_state.current_value < 1.0 || self.data().clips_to_bounds {
+            if self.data().collapse_state.value() < 1.0 || self.data().clips_to_bounds {
                 unsafe {
                     nvgSave(ctx.vg().raw());
                     nvgIntersectScissor(
@@ -84,20 +87,20 @@ pub trait ViewDrawer: ViewLayout {
                         x,
                         y,
                         width,
-                        height * self.data().collapse_state.current_value,
+                        height * self.data().collapse_state.value(),
                     );
                 }
             }
 
             // Draw the view
-            self.draw(ctx.vg(), x, y, width, height);
+            self.draw(ctx, x, y, width, height);
 
             if self.data().wireframe_enabled {
                 self.draw_wire_frame(ctx, &rect);
             }
 
             // Reset clipping
-            if self.data().collapse_state.current_value < 1.0 || self.data().clips_to_bounds {
+            if self.data().collapse_state.value() < 1.0 || self.data().clips_to_bounds {
                 unsafe {
                     nvgRestore(ctx.vg().raw());
                 }
@@ -113,7 +116,22 @@ pub trait ViewDrawer: ViewLayout {
      * Called each frame
      */
     fn frame_highlight(&self, ctx: &FrameContext) {
-        todo!()
+        if self.data().hide_highlight {
+            return;
+        }
+
+        unsafe {
+            nvgSave(ctx.vg().raw());
+            nvgResetScissor(ctx.vg().raw());
+        }
+
+        if !self.data().hide_highlight_border {
+            self.draw_highlight(ctx, &self.rect(), self.alpha(), false);
+        }
+
+        unsafe {
+            nvgRestore(ctx.vg().raw());
+        }
     }
 
     /**
@@ -121,7 +139,7 @@ pub trait ViewDrawer: ViewLayout {
      * Views should not draw outside of their bounds (they
      * may be clipped if they do so).
      */
-    fn draw(&self, vg: &Context, x: f32, y: f32, width: f32, height: f32) {}
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, width: f32, height: f32) {}
 
     /**
      * Called when the view will appear
@@ -175,9 +193,22 @@ pub trait ViewDrawer: ViewLayout {
         self.data_mut().fade_in = true;
 
         if animate {
-
+            let alpha = &self.data().alpha;
+            alpha.reset_initial(0.0);
+            alpha.add_step_easing(1.0, animation_duration as i32, EasingFunction::QuadraticOut);
+
+            let view = self.data().view.clone();
+            alpha.set_end_callback(Box::new(move |_finished| {
+                if let Some(view) = view.as_ref().and_then(|view| view.upgrade()) {
+                    let mut view = view.borrow_mut();
+                    view.data_mut().fade_in = false;
+                    view.on_show_animation_end();
+                }
+                cb();
+            }));
+            alpha.start();
         } else {
-            self.data_mut().alpha.current_value = 1.0;
+            self.data().alpha.reset_initial(1.0);
             self.data_mut().fade_in = false;
             self.on_show_animation_end();
             cb();
@@ -196,7 +227,7 @@ pub trait ViewDrawer: ViewLayout {
     }
 
     fn alpha(&self) -> c_float {
-        self.data().alpha.current_value
+        self.data().alpha.value()
     }
 
     fn a(&self, color: NVGcolor) -> NVGcolor {
@@ -345,7 +376,7 @@ pub trait ViewDrawer: ViewLayout {
                 rect.height(),
                 self.data().corner_radius * 2.0,
                 shadow_feather,
-                nvgRGBA(0, 0, 0, (shadow_opacity * self.data().alpha.current_value) as c_uchar),
+                nvgRGBA(0, 0, 0, (shadow_opacity * self.data().alpha.value()) as c_uchar),
                 transparent_color(),
             );
 
@@ -395,15 +426,192 @@ pub trait ViewDrawer: ViewLayout {
         }
 
         let vg = ctx.vg().raw();
+        let data = self.data();
+
+        let padding = data.highlight_padding;
+        let corner_radius = data.highlight_corner_radius;
+        let stroke_width = style("brls/highlight/stroke_width");
+        let highlight_alpha = data.highlight_alpha.value();
+
+        let mut x = rect.min_x() - padding - stroke_width / 2.0;
+        let mut y = rect.min_y() - padding - stroke_width / 2.0;
+        let width = rect.width() + padding * 2.0 + stroke_width;
+        let height = rect.height() + padding * 2.0 + stroke_width;
+
+        // Shake animation
+        if data.highlight_shake.is_running() {
+            let offset = shake_animation(data.highlight_shake.value(), data.highlight_shake_amplitude);
+            match data.highlight_shake_direction {
+                FocusDirection::Right => x += offset,
+                FocusDirection::Left => x -= offset,
+                FocusDirection::Down => y += offset,
+                FocusDirection::Up => y -= offset,
+            }
+        }
 
         unsafe {
             nvgSave(vg);
+            nvgResetScissor(vg);
+
+            if background {
+                // Background
+                let background_color = theme("brls/highlight/background");
+                nvgFillColor(
+                    vg,
+                    nvgRGBAf(
+                        background_color.rgba[0],
+                        background_color.rgba[1],
+                        background_color.rgba[2],
+                        highlight_alpha,
+                    ),
+                );
+                nvgBeginPath(vg);
+                nvgRoundedRect(vg, x, y, width, height, corner_radius);
+                nvgFill(vg);
+            } else {
+                // Shadow
+                let shadow_offset = style("brls/highlight/shadow_offset");
+                let shadow_paint = nvgBoxGradient(
+                    vg,
+                    x,
+                    y + style("brls/highlight/shadow_width"),
+                    width,
+                    height,
+                    corner_radius * 2.0,
+                    style("brls/highlight/shadow_feather"),
+                    nvgRGBA(
+                        0,
+                        0,
+                        0,
+                        (style("brls/highlight/shadow_opacity") * alpha * highlight_alpha) as c_uchar,
+                    ),
+                    transparent_color(),
+                );
+
+                nvgBeginPath(vg);
+                nvgRect(
+                    vg,
+                    x - shadow_offset,
+                    y - shadow_offset,
+                    width + shadow_offset * 2.0,
+                    height + shadow_offset * 3.0,
+                );
+                nvgRoundedRect(vg, x, y, width, height, corner_radius);
+                nvgPathWinding(vg, NVGsolidity::NVG_HOLE.bits());
+                nvgFillPaint(vg, shadow_paint);
+                nvgFill(vg);
+
+                // Border, pulsing between the two highlight colors
+                let (mut gradient_x, mut gradient_y, mut color) = (0.0, 0.0, 0.0);
+                highlight_animation(&mut gradient_x, &mut gradient_y, &mut color);
+
+                let color1 = theme("brls/highlight/color1");
+                let color2 = theme("brls/highlight/color2");
+                let pulsation_color = nvgRGBAf(
+                    color * color1.rgba[0] + (1.0 - color) * color2.rgba[0],
+                    color * color1.rgba[1] + (1.0 - color) * color2.rgba[1],
+                    color * color1.rgba[2] + (1.0 - color) * color2.rgba[2],
+                    alpha * highlight_alpha,
+                );
+
+                let mut border_color = color2;
+                border_color.rgba[3] = 0.5 * alpha * highlight_alpha;
+
+                let border1_paint = nvgRadialGradient(
+                    vg,
+                    x + gradient_x * width,
+                    y + gradient_y * height,
+                    stroke_width * 10.0,
+                    stroke_width * 40.0,
+                    border_color,
+                    transparent_color(),
+                );
+                let border2_paint = nvgRadialGradient(
+                    vg,
+                    x + (1.0 - gradient_x) * width,
+                    y + (1.0 - gradient_y) * height,
+                    stroke_width * 10.0,
+                    stroke_width * 40.0,
+                    border_color,
+                    transparent_color(),
+                );
+
+                nvgBeginPath(vg);
+                nvgStrokeColor(vg, pulsation_color);
+                nvgStrokeWidth(vg, stroke_width);
+                nvgRoundedRect(vg, x, y, width, height, corner_radius);
+                nvgStroke(vg);
+
+                for paint in [border1_paint, border2_paint] {
+                    nvgBeginPath(vg);
+                    nvgStrokePaint(vg, paint);
+                    nvgStrokeWidth(vg, stroke_width);
+                    nvgRoundedRect(vg, x, y, width, height, corner_radius);
+                    nvgStroke(vg);
+                }
+            }
 
             nvgRestore(vg);
         }
     }
 
-    fn draw_click_animation(&self, ctx: &FrameContext, rect: &Rect) {}
+    fn draw_click_animation(&self, ctx: &FrameContext, rect: &Rect) {
+        let vg = ctx.vg().raw();
+        let mut color = theme("brls/click_pulse");
+        color.rgba[3] *= self.data().click_alpha.value();
+
+        unsafe {
+            nvgFillColor(vg, self.a(color));
+            nvgBeginPath(vg);
+            if self.data().corner_radius > 0.0 {
+                nvgRoundedRect(
+                    vg,
+                    rect.min_x(),
+                    rect.min_y(),
+                    rect.width(),
+                    rect.height(),
+                    self.data().corner_radius,
+                );
+            } else {
+                nvgRect(vg, rect.min_x(), rect.min_y(), rect.width(), rect.height());
+            }
+            nvgFill(vg);
+        }
+    }
+
+    /**
+     * Plays the click pulse: fades the click color in then out.
+     * Reverse only fades it out.
+     */
+    fn play_click_animation(&mut self, reverse: bool) {
+        if self.data().hide_click_animation {
+            return;
+        }
+
+        let click_alpha = &self.data().click_alpha;
+        click_alpha.reset_initial(if reverse { 1.0 } else { 0.0 });
+        click_alpha.add_step_easing(
+            if reverse { 0.0 } else { 1.0 },
+            style("brls/animations/highlight") as i32,
+            if reverse {
+                EasingFunction::QuadraticOut
+            } else {
+                EasingFunction::QuadraticIn
+            },
+        );
+
+        let view = self.data().view.clone();
+        click_alpha.set_end_callback(Box::new(move |finished| {
+            // Interrupted pulses are replaced by a new one
+            if reverse || !finished {
+                return;
+            }
+            if let Some(view) = view.as_ref().and_then(|view| view.upgrade()) {
+                view.borrow_mut().play_click_animation(true);
+            }
+        }));
+        click_alpha.start();
+    }
 
     fn draw_wire_frame(&self, ctx: &FrameContext, rect: &Rect) {
         let vg = ctx.vg().raw();
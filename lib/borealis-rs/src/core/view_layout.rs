@@ -1,7 +1,11 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use crate::core::animation::Animating;
+use crate::core::attribute::XMLAttributes;
+use crate::core::style::style;
+use crate::core::time::Ticking;
 use crate::core::geometry::{Point, Rect, Size};
-use crate::core::theme::{AUTO, YG_UNDEFINED};
+use crate::core::theme::YG_UNDEFINED;
 use crate::core::view_base::{AlignSelf, FocusDirection, PositionType, View, ViewBackground, ViewBase, Visibility};
 use yoga_sys::YGAlign::{
     YGAlignAuto, YGAlignBaseline, YGAlignCenter, YGAlignFlexEnd, YGAlignFlexStart,
@@ -9,18 +13,33 @@ use yoga_sys::YGAlign::{
 };
 use yoga_sys::YGPositionType::{YGPositionTypeAbsolute, YGPositionTypeRelative};
 use yoga_sys::{
-    YGDirection, YGEdge, YGNodeCalculateLayout, YGNodeLayoutGetHeight, YGNodeLayoutGetLeft,
-    YGNodeLayoutGetTop, YGNodeLayoutGetWidth, YGNodeStyleSetAlignSelf, YGNodeStyleSetFlexGrow,
-    YGNodeStyleSetFlexShrink, YGNodeStyleSetHeight, YGNodeStyleSetHeightAuto,
-    YGNodeStyleSetMinHeight, YGNodeStyleSetMinHeightPercent, YGNodeStyleSetMinWidth,
-    YGNodeStyleSetMinWidthPercent, YGNodeStyleSetPosition, YGNodeStyleSetPositionPercent,
-    YGNodeStyleSetPositionType, YGNodeStyleSetWidth, YGNodeStyleSetWidthAuto,
+    YGDirection, YGDisplay, YGEdge, YGNodeCalculateLayout, YGNodeLayoutGetHeight, YGNodeLayoutGetLeft,
+    YGNodeLayoutGetTop, YGNodeLayoutGetWidth, YGNodeStyleGetMargin, YGNodeStyleSetAlignSelf, YGNodeStyleSetDisplay,
+    YGNodeStyleSetFlexGrow, YGNodeStyleSetFlexShrink, YGNodeStyleSetHeight,
+    YGNodeStyleSetHeightAuto, YGNodeStyleSetHeightPercent, YGNodeStyleSetMargin,
+    YGNodeStyleSetMarginAuto, YGNodeStyleSetMinHeight, YGNodeStyleSetMinHeightPercent,
+    YGNodeStyleSetMinWidth, YGNodeStyleSetMinWidthPercent, YGNodeStyleSetPosition,
+    YGNodeStyleSetPositionPercent, YGNodeStyleSetPositionType, YGNodeStyleSetWidth,
+    YGNodeStyleSetWidthAuto, YGNodeStyleSetWidthPercent, YGNodeRef,
 };
 use crate::core::view_style::ViewStyle;
 
 pub trait ViewLayout: ViewStyle {
-    fn shake_highlight(&self, direction: FocusDirection) {
-        todo!()
+    /**
+     * Shakes the highlight in the given direction, to show
+     * that the focus cannot go further that way.
+     */
+    fn shake_highlight(&mut self, direction: FocusDirection) {
+        let duration = style("brls/animations/highlight_shake");
+
+        let data = self.data_mut();
+        data.highlight_shake_direction = direction;
+        // Not random, so that replays draw the same frames
+        data.highlight_shake_amplitude = style("brls/highlight/shake_amplitude");
+        data.highlight_shake.reset_initial(0.0);
+        data.highlight_shake
+            .add_step(duration, (duration * 10.0) as i32);
+        data.highlight_shake.start();
     }
 
     fn rect(&self) -> Rect {
@@ -30,12 +49,24 @@ pub trait ViewLayout: ViewStyle {
         );
     }
 
+    /**
+     * Returns the X position of the view on screen,
+     * see local_x() for the position inside the parent.
+     * The parents are borrowed, none of them can be mutably borrowed.
+     */
     fn x(&self) -> f32 {
-        return unsafe { YGNodeLayoutGetLeft(self.data().yg_node) };
+        let parent_x = self.parent().map_or(0.0, |parent| parent.borrow().x());
+        parent_x + self.local_x()
     }
 
+    /**
+     * Returns the Y position of the view on screen,
+     * see local_y() for the position inside the parent.
+     * The parents are borrowed, none of them can be mutably borrowed.
+     */
     fn y(&self) -> f32 {
-        return unsafe { YGNodeLayoutGetTop(self.data().yg_node) };
+        let parent_y = self.parent().map_or(0.0, |parent| parent.borrow().y());
+        parent_y + self.local_y()
     }
 
     fn local_rect(&self) -> Rect {
@@ -73,8 +104,8 @@ pub trait ViewLayout: ViewStyle {
      * call this method.
      */
     fn invalidate(&self) {
-        if self.has_parent() {
-            self.parent().as_ref().unwrap().borrow().invalidate();
+        if let Some(parent) = self.parent() {
+            parent.borrow().invalidate();
         } else {
             unsafe {
                 YGNodeCalculateLayout(
@@ -92,15 +123,33 @@ pub trait ViewLayout: ViewStyle {
      */
     fn on_layout(&self) {}
 
+    /**
+     * Returns the view under the given point, in screen coordinates,
+     * or None if the point is outside of the view or the view is hidden.
+     */
+    fn hit_test(&self, point: &Point) -> Option<Rc<RefCell<View>>> {
+        if self.data().visibility != Visibility::Visible || self.data().alpha.value() == 0.0 {
+            return None;
+        }
+
+        match self.rect().contains(point) {
+            true => self.view(),
+            false => None,
+        }
+    }
+
     /**
      * Returns the view with the corresponding id in the view or its children,
-     * or nullptr if it hasn't been found.
+     * or None if it hasn't been found.
      *
      * Research is done recursively by traversing the tree starting from this view.
      * This view's parents are not traversed.
      */
-    fn get_view(&self, id: &str) -> Rc<RefCell<View>> {
-        todo!()
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self.data().id == id {
+            true => self.view(),
+            false => None,
+        }
     }
 
     // -----------------------------------------------------------
@@ -117,8 +166,16 @@ pub trait ViewLayout: ViewStyle {
     fn set_width(&self, width: f32) {
         unsafe {
             YGNodeStyleSetMinWidthPercent(self.data().yg_node, 0.0);
-            YGNodeStyleSetWidth(self.data().yg_node, width);
-            YGNodeStyleSetMinWidth(self.data().yg_node, width);
+            match width.is_nan() {
+                true => {
+                    YGNodeStyleSetWidthAuto(self.data().yg_node);
+                    YGNodeStyleSetMinWidth(self.data().yg_node, YG_UNDEFINED);
+                }
+                false => {
+                    YGNodeStyleSetWidth(self.data().yg_node, width);
+                    YGNodeStyleSetMinWidth(self.data().yg_node, width);
+                }
+            }
         }
         self.invalidate();
     }
@@ -133,8 +190,16 @@ pub trait ViewLayout: ViewStyle {
     fn set_height(&self, height: f32) {
         unsafe {
             YGNodeStyleSetMinHeightPercent(self.data().yg_node, 0.0);
-            YGNodeStyleSetHeight(self.data().yg_node, height);
-            YGNodeStyleSetMinHeight(self.data().yg_node, height);
+            match height.is_nan() {
+                true => {
+                    YGNodeStyleSetHeightAuto(self.data().yg_node);
+                    YGNodeStyleSetMinHeight(self.data().yg_node, YG_UNDEFINED);
+                }
+                false => {
+                    YGNodeStyleSetHeight(self.data().yg_node, height);
+                    YGNodeStyleSetMinHeight(self.data().yg_node, height);
+                }
+            }
         }
         self.invalidate();
     }
@@ -161,7 +226,7 @@ pub trait ViewLayout: ViewStyle {
             YGNodeStyleSetMinWidthPercent(self.data().yg_node, 0.0);
             YGNodeStyleSetMinHeightPercent(self.data().yg_node, 0.0);
 
-            match width == AUTO {
+            match width.is_nan() {
                 true => {
                     YGNodeStyleSetWidthAuto(self.data().yg_node);
                     YGNodeStyleSetMinWidth(self.data().yg_node, YG_UNDEFINED);
@@ -172,7 +237,7 @@ pub trait ViewLayout: ViewStyle {
                 }
             }
 
-            match height == AUTO {
+            match height.is_nan() {
                 true => {
                     YGNodeStyleSetHeightAuto(self.data().yg_node);
                     YGNodeStyleSetMinHeight(self.data().yg_node, YG_UNDEFINED);
@@ -192,7 +257,11 @@ pub trait ViewLayout: ViewStyle {
      * the parent view width. Between 0.0f and 100.0f.
      */
     fn set_width_percentage(&self, percentage: f32) {
-        todo!()
+        unsafe {
+            YGNodeStyleSetWidthPercent(self.data().yg_node, percentage);
+            YGNodeStyleSetMinWidthPercent(self.data().yg_node, percentage);
+        }
+        self.invalidate();
     }
 
     /**
@@ -200,7 +269,11 @@ pub trait ViewLayout: ViewStyle {
      * the parent view height. Between 0.0f and 100.0f.
      */
     fn set_height_percentage(&self, percentage: f32) {
-        todo!()
+        unsafe {
+            YGNodeStyleSetHeightPercent(self.data().yg_node, percentage);
+            YGNodeStyleSetMinHeightPercent(self.data().yg_node, percentage);
+        }
+        self.invalidate();
     }
 
     /**
@@ -338,7 +411,11 @@ pub trait ViewLayout: ViewStyle {
      * Only does one layout pass instead of four when using the four methods separately.
      */
     fn set_margins(&self, top: f32, right: f32, bottom: f32, left: f32) {
-        todo!()
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeTop, top);
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeRight, right);
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeBottom, bottom);
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeLeft, left);
+        self.invalidate();
     }
 
     /**
@@ -352,7 +429,8 @@ pub trait ViewLayout: ViewStyle {
      * margin.
      */
     fn set_margin_top(&self, top: f32) {
-        todo!()
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeTop, top);
+        self.invalidate();
     }
 
     /**
@@ -366,14 +444,15 @@ pub trait ViewLayout: ViewStyle {
      * margin.
      */
     fn set_margin_right(&self, right: f32) {
-        todo!()
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeRight, right);
+        self.invalidate();
     }
 
     fn margin_right(&self) -> f32 {
-        todo!()
+        unsafe { YGNodeStyleGetMargin(self.data().yg_node, YGEdge::YGEdgeRight).value }
     }
     fn margin_left(&self) -> f32 {
-        todo!()
+        unsafe { YGNodeStyleGetMargin(self.data().yg_node, YGEdge::YGEdgeLeft).value }
     }
 
     /**
@@ -386,8 +465,9 @@ pub trait ViewLayout: ViewStyle {
      * Use brls::View::AUTO to have the layout automatically select the
      * margin.
      */
-    fn set_margin_bottom(&self, right: f32) {
-        todo!()
+    fn set_margin_bottom(&self, bottom: f32) {
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeBottom, bottom);
+        self.invalidate();
     }
 
 
@@ -402,21 +482,38 @@ pub trait ViewLayout: ViewStyle {
      * margin.
      */
     fn set_margin_left(&self, left: f32) {
-        todo!()
+        set_margin_edge(self.data().yg_node, YGEdge::YGEdgeLeft, left);
+        self.invalidate();
     }
 
     /**
      * Sets the visibility of the view.
      */
-    fn set_visibility(&self, visibility: Visibility) {
+    fn set_visibility(&mut self, visibility: Visibility) {
+        // Only change YG properties and invalidate if going from or to GONE
+        let was_gone = self.data().visibility == Visibility::Gone;
+        let is_gone = visibility == Visibility::Gone;
+        self.data_mut().visibility = visibility;
 
+        if was_gone != is_gone {
+            unsafe {
+                YGNodeStyleSetDisplay(
+                    self.data().yg_node,
+                    match is_gone {
+                        true => YGDisplay::YGDisplayNone,
+                        false => YGDisplay::YGDisplayFlex,
+                    },
+                );
+            }
+            self.invalidate();
+        }
     }
 
     /**
      * Gets the visibility of the view.
      */
     fn visibility(&self) -> Visibility {
-        todo!()
+        self.data().visibility
     }
 
 
@@ -436,7 +533,7 @@ pub trait ViewLayout: ViewStyle {
      */
     fn set_position_top(&self, pos: f32) {
         unsafe {
-            match pos == AUTO {
+            match pos.is_nan() {
                 true => {
                     YGNodeStyleSetPosition(self.data().yg_node, YGEdge::YGEdgeTop, YG_UNDEFINED)
                 }
@@ -462,7 +559,7 @@ pub trait ViewLayout: ViewStyle {
      */
     fn set_position_right(&self, pos: f32) {
         unsafe {
-            match pos == AUTO {
+            match pos.is_nan() {
                 true => {
                     YGNodeStyleSetPosition(self.data().yg_node, YGEdge::YGEdgeRight, YG_UNDEFINED)
                 }
@@ -488,7 +585,7 @@ pub trait ViewLayout: ViewStyle {
      */
     fn set_position_bottom(&self, pos: f32) {
         unsafe {
-            match pos == AUTO {
+            match pos.is_nan() {
                 true => {
                     YGNodeStyleSetPosition(self.data().yg_node, YGEdge::YGEdgeBottom, YG_UNDEFINED)
                 }
@@ -514,7 +611,7 @@ pub trait ViewLayout: ViewStyle {
      */
     fn set_position_left(&self, pos: f32) {
         unsafe {
-            match pos == AUTO {
+            match pos.is_nan() {
                 true => {
                     YGNodeStyleSetPosition(self.data().yg_node, YGEdge::YGEdgeLeft, YG_UNDEFINED)
                 }
@@ -599,8 +696,8 @@ pub trait ViewLayout: ViewStyle {
     /**
      * Sets the id of the view.
      */
-    fn set_id(&self, id: &str) {
-        todo!()
+    fn set_id(&mut self, id: &str) {
+        self.data_mut().id = id.into();
     }
 
     /**
@@ -632,3 +729,100 @@ pub trait ViewLayout: ViewStyle {
         self.invalidate();
     }
 }
+
+/// Attributes backed by the ViewLayout setters.
+pub fn register_layout_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_string_attribute("id", |view, value| view.set_id(value));
+
+    attributes.register_auto_float_attribute("width", |view, value| view.set_width(value));
+    attributes.register_percentage_attribute("width", |view, value| {
+        view.set_width_percentage(value)
+    });
+    attributes.register_auto_float_attribute("height", |view, value| view.set_height(value));
+    attributes.register_percentage_attribute("height", |view, value| {
+        view.set_height_percentage(value)
+    });
+
+    attributes.register_float_attribute("grow", |view, value| view.set_grow(value));
+    attributes.register_float_attribute("shrink", |view, value| view.set_shrink(value));
+
+    attributes.register_auto_float_attribute("margin", |view, value| {
+        view.set_margins(value, value, value, value)
+    });
+    attributes.register_auto_float_attribute("marginTop", |view, value| view.set_margin_top(value));
+    attributes.register_auto_float_attribute("marginRight", |view, value| {
+        view.set_margin_right(value)
+    });
+    attributes.register_auto_float_attribute("marginBottom", |view, value| {
+        view.set_margin_bottom(value)
+    });
+    attributes.register_auto_float_attribute("marginLeft", |view, value| {
+        view.set_margin_left(value)
+    });
+
+    attributes.register_auto_float_attribute("positionTop", |view, value| {
+        view.set_position_top(value)
+    });
+    attributes.register_percentage_attribute("positionTop", |view, value| {
+        view.set_position_top_percentage(value)
+    });
+    attributes.register_auto_float_attribute("positionRight", |view, value| {
+        view.set_position_right(value)
+    });
+    attributes.register_percentage_attribute("positionRight", |view, value| {
+        view.set_position_right_percentage(value)
+    });
+    attributes.register_auto_float_attribute("positionBottom", |view, value| {
+        view.set_position_bottom(value)
+    });
+    attributes.register_percentage_attribute("positionBottom", |view, value| {
+        view.set_position_bottom_percentage(value)
+    });
+    attributes.register_auto_float_attribute("positionLeft", |view, value| {
+        view.set_position_left(value)
+    });
+    attributes.register_percentage_attribute("positionLeft", |view, value| {
+        view.set_position_left_percentage(value)
+    });
+
+    attributes.register_enum_attribute(
+        "positionType",
+        &[
+            ("relative", PositionType::Relative),
+            ("absolute", PositionType::Absolute),
+        ],
+        |view, value| view.set_position_type(value),
+    );
+    attributes.register_enum_attribute(
+        "alignSelf",
+        &[
+            ("auto", AlignSelf::Auto),
+            ("flexStart", AlignSelf::FlexStart),
+            ("center", AlignSelf::Center),
+            ("flexEnd", AlignSelf::FlexEnd),
+            ("stretch", AlignSelf::Stretch),
+            ("baseline", AlignSelf::Baseline),
+            ("spaceBetween", AlignSelf::SpaceBetween),
+            ("spaceAround", AlignSelf::SpaceAround),
+        ],
+        |view, value| view.set_align_self(value),
+    );
+    attributes.register_enum_attribute(
+        "visibility",
+        &[
+            ("visible", Visibility::Visible),
+            ("invisible", Visibility::Invisible),
+            ("gone", Visibility::Gone),
+        ],
+        |view, value| view.set_visibility(value),
+    );
+}
+
+fn set_margin_edge(node: YGNodeRef, edge: YGEdge, value: f32) {
+    unsafe {
+        match value.is_nan() {
+            true => YGNodeStyleSetMarginAuto(node, edge),
+            false => YGNodeStyleSetMargin(node, edge, value),
+        }
+    }
+}
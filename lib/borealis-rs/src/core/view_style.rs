@@ -1,4 +1,5 @@
 use nanovg_sys::NVGcolor;
+use crate::core::attribute::XMLAttributes;
 use crate::core::view_base::{ShadowType, ViewBackground, ViewBase};
 
 /// -----------------------------------------------------------
@@ -157,4 +158,51 @@ pub trait ViewStyle: ViewBase {
     {
         self.data_mut().highlight_corner_radius = radius;
     }
-}
\ No newline at end of file
+}
+
+/// Attributes backed by the ViewStyle setters.
+pub fn register_style_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_color_attribute("backgroundColor", |view, value| {
+        view.set_background_color(value)
+    });
+    attributes.register_color_attribute("borderColor", |view, value| view.set_border_color(value));
+    attributes.register_float_attribute("borderThickness", |view, value| {
+        view.set_border_thickness(value)
+    });
+    attributes.register_float_attribute("cornerRadius", |view, value| view.set_corner_radius(value));
+
+    attributes.register_color_attribute("lineColor", |view, value| view.set_line_color(value));
+    attributes.register_float_attribute("lineTop", |view, value| view.set_line_top(value));
+    attributes.register_float_attribute("lineRight", |view, value| view.set_line_right(value));
+    attributes.register_float_attribute("lineBottom", |view, value| view.set_line_bottom(value));
+    attributes.register_float_attribute("lineLeft", |view, value| view.set_line_left(value));
+
+    attributes.register_enum_attribute(
+        "shadowType",
+        &[
+            ("none", ShadowType::None),
+            ("generic", ShadowType::Generic),
+            ("custom", ShadowType::Custom),
+        ],
+        |view, value| view.set_shadow_type(value),
+    );
+
+    attributes.register_bool_attribute("hideHighlightBackground", |view, value| {
+        view.set_hide_highlight_background(value)
+    });
+    attributes.register_bool_attribute("hideHighlightBorder", |view, value| {
+        view.set_hide_highlight_border(value)
+    });
+    attributes.register_bool_attribute("hideHighlight", |view, value| {
+        view.set_hide_highlight(value)
+    });
+    attributes.register_bool_attribute("hideClickAnimation", |view, value| {
+        view.set_hide_click_animation(value)
+    });
+    attributes.register_float_attribute("highlightPadding", |view, value| {
+        view.set_highlight_padding(value)
+    });
+    attributes.register_float_attribute("highlightCornerRadius", |view, value| {
+        view.set_highlight_corner_radius(value)
+    });
+}
@@ -0,0 +1,229 @@
+use crate::core::input::{ControllerAxis, ControllerButton, ControllerState, InputBackend};
+use crate::core::time::Time;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A button held for a given time, from the first poll following the hold.
+struct Hold {
+    button: ControllerButton,
+    duration: Time,
+    until: Option<Time>,
+}
+
+#[derive(Default)]
+struct VirtualGamepadState {
+    state: ControllerState,
+    taps: Vec<ControllerButton>,
+    /// Buttons tapped at the last poll, released at the next one
+    tapped: Vec<ControllerButton>,
+    holds: Vec<Hold>,
+}
+
+/// A gamepad driven by code, to test the focus navigation
+/// and the actions of a screen without any hardware.
+///
+/// Clones share the same gamepad, so a test can keep one
+/// to drive the one given to the application:
+///
+/// let gamepad = VirtualGamepad::new();
+/// application.add_input_backend(Box::new(gamepad.clone()));
+/// gamepad.tap(ControllerButton::Down);
+/// application.internal_main_loop();
+#[derive(Default, Clone)]
+pub struct VirtualGamepad {
+    state: Rc<RefCell<VirtualGamepadState>>,
+}
+
+impl VirtualGamepad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Presses the button until release() is called.
+     */
+    pub fn press(&self, button: ControllerButton) {
+        self.state.borrow_mut().state.set_pressed(button, true);
+    }
+
+    pub fn release(&self, button: ControllerButton) {
+        let mut state = self.state.borrow_mut();
+        state.state.set_pressed(button, false);
+        state.holds.retain(|hold| hold.button != button);
+    }
+
+    /**
+     * Releases every button and centers the sticks.
+     */
+    pub fn release_all(&self) {
+        *self.state.borrow_mut() = VirtualGamepadState::default();
+    }
+
+    /**
+     * Presses the button for a single poll. Taps of the same button
+     * are two polls apart, the button being released in between.
+     */
+    pub fn tap(&self, button: ControllerButton) {
+        self.state.borrow_mut().taps.push(button);
+    }
+
+    /**
+     * Presses the button for the given time in ms, counted
+     * from the next time the application polls the gamepad.
+     */
+    pub fn hold(&self, button: ControllerButton, duration: Time) {
+        self.state.borrow_mut().holds.push(Hold {
+            button,
+            duration,
+            until: None,
+        });
+    }
+
+    /**
+     * Moves a stick, from -1.0 (left or up) to 1.0 (right or down).
+     */
+    pub fn set_axis(&self, axis: ControllerAxis, value: f32) {
+        self.state.borrow_mut().state.set_axis(axis, value);
+    }
+
+    pub fn axis(&self, axis: ControllerAxis) -> f32 {
+        self.state.borrow().state.axis(axis)
+    }
+
+    /**
+     * Returns true if the button is pressed, held or tapped.
+     */
+    pub fn is_pressed(&self, button: ControllerButton) -> bool {
+        let state = self.state.borrow();
+        state.state.is_pressed(button)
+            || state.taps.contains(&button)
+            || state.holds.iter().any(|hold| hold.button == button)
+    }
+}
+
+impl InputBackend for VirtualGamepad {
+    fn update_controller_state(&mut self, now: Time, state: &mut ControllerState) {
+        let mut gamepad = self.state.borrow_mut();
+        *state = gamepad.state.clone();
+
+        let taps = std::mem::take(&mut gamepad.taps);
+        let mut tapped = vec![];
+        for button in taps {
+            if gamepad.tapped.contains(&button) || tapped.contains(&button) {
+                gamepad.taps.push(button);
+            } else {
+                state.set_pressed(button, true);
+                tapped.push(button);
+            }
+        }
+        gamepad.tapped = tapped;
+
+        gamepad.holds.retain_mut(|hold| {
+            let until = *hold.until.get_or_insert(now + hold.duration * 1000);
+            if now >= until {
+                return false;
+            }
+
+            state.set_pressed(hold.button, true);
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualGamepad;
+    use crate::core::application::Application;
+    use crate::core::input::{ControllerButton, ControllerState, InputBackend};
+    use crate::core::time::ManualClock;
+    use crate::core::view_base::ViewBase;
+    use crate::core::view_creator::{XMLParser, XMLViewRegistry};
+    use crate::core::view_layout::ViewLayout;
+
+    fn poll(gamepad: &mut VirtualGamepad, now: u128) -> bool {
+        let mut state = ControllerState::default();
+        gamepad.update_controller_state(now, &mut state);
+        state.is_pressed(ControllerButton::A)
+    }
+
+    #[test]
+    fn tap_lasts_one_poll() {
+        let mut gamepad = VirtualGamepad::new();
+        gamepad.tap(ControllerButton::A);
+        assert!(gamepad.is_pressed(ControllerButton::A));
+
+        assert!(poll(&mut gamepad, 0));
+        assert!(!poll(&mut gamepad, 16_000));
+        assert!(!gamepad.is_pressed(ControllerButton::A));
+    }
+
+    #[test]
+    fn taps_of_the_same_button_are_two_polls_apart() {
+        let mut gamepad = VirtualGamepad::new();
+        gamepad.tap(ControllerButton::A);
+        gamepad.tap(ControllerButton::A);
+
+        assert!(poll(&mut gamepad, 0));
+        assert!(!poll(&mut gamepad, 16_000));
+        assert!(poll(&mut gamepad, 32_000));
+        assert!(!poll(&mut gamepad, 48_000));
+    }
+
+    #[test]
+    fn hold_starts_at_the_next_poll() {
+        let mut gamepad = VirtualGamepad::new();
+        gamepad.hold(ControllerButton::A, 100);
+
+        assert!(poll(&mut gamepad, 1_000_000));
+        assert!(poll(&mut gamepad, 1_099_999));
+        assert!(!poll(&mut gamepad, 1_100_000));
+        assert!(!gamepad.is_pressed(ControllerButton::A));
+    }
+
+    #[test]
+    fn navigates_headless() {
+        let clock = ManualClock::new(1_000_000);
+        let mut application = Application::new("test").unwrap();
+        application.set_clock(Box::new(clock.clone()));
+
+        let registry = XMLViewRegistry::new();
+        let xml = r#"<brls:Box axis="column">
+            <brls:Label id="first" focusable="true"/>
+            <brls:Label id="second" focusable="true"/>
+            <brls:Label id="third" focusable="true"/>
+        </brls:Box>"#;
+        let root = XMLParser::new(&registry, "<test>".into(), xml)
+            .parse()
+            .unwrap();
+        let first = root.borrow().get_view("first");
+        application.give_focus(first);
+
+        let gamepad = VirtualGamepad::new();
+        application.add_input_backend(Box::new(gamepad.clone()));
+        let focus = |application: &Application| {
+            application
+                .current_focus()
+                .unwrap()
+                .borrow()
+                .data()
+                .id
+                .clone()
+        };
+        let frame = |application: &mut Application| {
+            clock.advance(16);
+            application.internal_main_loop();
+        };
+
+        gamepad.tap(ControllerButton::Down);
+        gamepad.tap(ControllerButton::Down);
+        frame(&mut application);
+        assert_eq!(focus(&application), "second");
+        frame(&mut application);
+        frame(&mut application);
+        assert_eq!(focus(&application), "third");
+
+        gamepad.tap(ControllerButton::Up);
+        frame(&mut application);
+        assert_eq!(focus(&application), "second");
+    }
+}
@@ -5,6 +5,9 @@ extern crate log;
 extern crate lazy_static;
 
 extern crate yoga_sys;
+
+// Lets the derive macros refer to this crate as borealis_rs from inside it too
+extern crate self as borealis_rs;
+
 pub mod core;
-pub mod demo;
 pub mod views;
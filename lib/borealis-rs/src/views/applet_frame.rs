@@ -1,31 +1,125 @@
-use crate::core::view_base::{ViewBase, ViewData};
-use crate::core::view_box::BoxTrait;
+use crate::core::attribute::XMLAttributes;
+use crate::core::frame_context::FrameContext;
+use crate::core::style::style;
+use crate::core::view_base::{View, ViewBase, ViewData, Visibility};
+use crate::core::view_box::{Axis, BoxEnum, BoxTrait, BoxView, BoxViewData};
 use crate::core::view_drawer::ViewDrawer;
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
+use crate::views::hint::Hints;
+use std::cell::RefCell;
+use std::rc::Rc;
 
-pub struct AppletFrame;
+/// The frame of an applet: its content on top, and a footer
+/// showing the hints of the actions available from the focused view.
+///
+/// Views added to the frame (from XML for instance) go above the footer.
+pub struct AppletFrame {
+    box_view_data: BoxViewData,
+    footer: Rc<RefCell<View>>,
+}
+
+impl AppletFrame {
+    pub fn new() -> Self {
+        let mut footer = BoxView::with_axis(Axis::Row);
+        footer.set_height(style("brls/applet_frame/footer_height"));
+        footer.set_padding_full(
+            style("brls/applet_frame/footer_padding_top_bottom"),
+            style("brls/applet_frame/footer_padding_sides"),
+            style("brls/applet_frame/footer_padding_top_bottom"),
+            style("brls/applet_frame/footer_padding_sides"),
+        );
+        let hints = View::Box(BoxEnum::Hints(Hints::new())).into_rc();
+        hints.borrow().set_grow(1.0);
+        footer.add_view(hints);
+        let footer = View::Box(BoxEnum::Box(footer)).into_rc();
+
+        let mut s = Self {
+            box_view_data: BoxViewData::default(),
+            footer: footer.clone(),
+        };
+        s.set_axis(Axis::Column);
+        s.add_view_position(footer, 0);
+        s
+    }
+
+    pub fn footer(&self) -> Rc<RefCell<View>> {
+        self.footer.clone()
+    }
+
+    pub fn set_footer_hidden(&self, hidden: bool) {
+        self.footer.borrow_mut().set_visibility(match hidden {
+            true => Visibility::Gone,
+            false => Visibility::Visible,
+        });
+    }
+}
 
 pub trait AppletFrameTrait: BoxTrait {
 
 }
 
-impl BoxTrait for AppletFrame {}
+impl BoxTrait for AppletFrame {
+    fn box_view_data(&self) -> &BoxViewData {
+        &self.box_view_data
+    }
 
-impl ViewDrawer for AppletFrame {}
+    fn box_view_data_mut(&mut self) -> &mut BoxViewData {
+        &mut self.box_view_data
+    }
 
-impl ViewLayout for AppletFrame {}
+    /**
+     * Adds the view above the footer, taking the remaining height.
+     */
+    fn add_view(&mut self, view: Rc<RefCell<View>>) {
+        view.borrow().set_grow(1.0);
+        let position = self.children().len() - 1;
+        self.add_view_position(view, position);
+    }
+}
+
+impl ViewDrawer for AppletFrame {
+    fn draw(&self, ctx: &FrameContext, _x: f32, _y: f32, _width: f32, _height: f32) {
+        self.frame_children(ctx);
+    }
+
+    fn will_appear(&self, reset_state: bool) {
+        self.children_will_appear(reset_state);
+    }
+
+    fn will_disappear(&self, reset_state: bool) {
+        self.children_will_disappear(reset_state);
+    }
+}
+
+impl ViewLayout for AppletFrame {
+    fn get_view(&self, id: &str) -> Option<Rc<RefCell<View>>> {
+        match self.data().id == id {
+            true => self.view(),
+            false => self.get_child_view(id),
+        }
+    }
+}
 
 impl ViewStyle for AppletFrame {}
 
 impl ViewBase for AppletFrame {
     fn data(&self) -> &ViewData {
-        todo!()
+        self.box_view_data.view_data()
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        todo!()
+        self.box_view_data.view_data_mut()
     }
 }
 
-impl AppletFrameTrait for AppletFrame{}
\ No newline at end of file
+impl AppletFrameTrait for AppletFrame{}
+
+/// Attributes only accepted by applet frames.
+pub fn register_applet_frame_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_bool_attribute("footerHidden", |view, value| {
+        if let View::Box(BoxEnum::AppletFrame(frame)) = view {
+            frame.set_footer_hidden(value);
+        }
+    });
+}
@@ -1,16 +1,136 @@
-use crate::core::view_base::{ViewBase, ViewData};
-use crate::core::view_box::BoxTrait;
+use crate::core::actions::{hint_actions, Action};
+use crate::core::frame_context::FrameContext;
+use crate::core::input::ControllerButton;
+use crate::core::style::style;
+use crate::core::theme::theme;
+use crate::core::view_base::{ShadowType, View, ViewBackground, ViewBase, ViewData};
+use crate::core::view_box::{Axis, BoxTrait, BoxViewData, JustifyContent};
 use crate::core::view_drawer::ViewDrawer;
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
+use nanovg::Context;
+use nanovg_sys::{nvgFillColor, nvgFontSize, nvgText, nvgTextBounds};
+use std::cell::RefCell;
+use std::ffi::c_char;
+use std::rc::Rc;
+
+/// The hint of an action: its button and what it does.
+pub struct Hint {
+    box_view_data: BoxViewData,
+    button: ControllerButton,
+    hint_text: String,
+    available: bool,
+}
+
+impl Hint {
+    pub fn new(action: &Action) -> Self {
+        let mut s = Self {
+            box_view_data: BoxViewData::default(),
+            button: action.button,
+            hint_text: action.hint_text.clone(),
+            available: action.available,
+        };
+        s.set_background(ViewBackground::None);
+        s.data_mut().shadow_type = ShadowType::None;
+        s
+    }
+
+    pub fn button(&self) -> ControllerButton {
+        self.button
+    }
+
+    pub fn hint_text(&self) -> &str {
+        &self.hint_text
+    }
+
+    /**
+     * Returns the text displayed for the given button.
+     */
+    pub fn button_label(button: ControllerButton) -> &'static str {
+        match button {
+            ControllerButton::A => "A",
+            ControllerButton::B => "B",
+            ControllerButton::X => "X",
+            ControllerButton::Y => "Y",
+            ControllerButton::Up => "Up",
+            ControllerButton::Down => "Down",
+            ControllerButton::Left => "Left",
+            ControllerButton::Right => "Right",
+            ControllerButton::L => "L",
+            ControllerButton::R => "R",
+            ControllerButton::Start => "Start",
+            ControllerButton::Select => "Select",
+        }
+    }
+
+    fn text(&self) -> String {
+        format!("{}  {}", Hint::button_label(self.button), self.hint_text)
+    }
+
+    /**
+     * Returns the width the hint takes once drawn.
+     */
+    pub fn content_width(&self, vg: &Context) -> f32 {
+        let text = self.text();
+        let mut bounds = [0.0; 4];
+        unsafe {
+            let start = text.as_ptr() as *const c_char;
+            nvgFontSize(vg.raw(), style("brls/hints/font_size"));
+            nvgTextBounds(
+                vg.raw(),
+                0.0,
+                0.0,
+                start,
+                start.add(text.len()),
+                bounds.as_mut_ptr(),
+            )
+        }
+    }
 
-pub struct Hint;
+    /**
+     * Draws the hint starting at x, vertically centered on center_y.
+     */
+    pub fn draw_content(&self, vg: &Context, x: f32, center_y: f32) {
+        let text = self.text();
+        let color = match self.available {
+            true => theme("brls/text"),
+            false => theme("brls/text_disabled"),
+        };
+
+        let mut bounds = [0.0; 4];
+        unsafe {
+            let start = text.as_ptr() as *const c_char;
+            let end = start.add(text.len());
+            nvgFontSize(vg.raw(), style("brls/hints/font_size"));
+            nvgTextBounds(vg.raw(), 0.0, 0.0, start, end, bounds.as_mut_ptr());
+
+            // Texts are drawn from their baseline
+            let baseline = center_y - (bounds[1] + bounds[3]) / 2.0;
+            nvgFillColor(vg.raw(), self.a(color));
+            nvgText(vg.raw(), x, baseline, start, end);
+        }
+    }
+}
 
 pub trait HintTrait: BoxTrait {}
 
-impl BoxTrait for Hint {}
+impl HintTrait for Hint {}
+
+impl BoxTrait for Hint {
+    fn box_view_data(&self) -> &BoxViewData {
+        &self.box_view_data
+    }
+
+    fn box_view_data_mut(&mut self) -> &mut BoxViewData {
+        &mut self.box_view_data
+    }
+}
 
-impl ViewDrawer for Hint {}
+impl ViewDrawer for Hint {
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, _width: f32, height: f32) {
+        self.draw_content(ctx.vg(), x, y + height / 2.0);
+    }
+}
 
 impl ViewLayout for Hint {}
 
@@ -18,25 +138,85 @@ impl ViewStyle for Hint {}
 
 impl ViewBase for Hint {
     fn data(&self) -> &ViewData {
-        todo!()
+        self.box_view_data.view_data()
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        todo!()
+        self.box_view_data.view_data_mut()
     }
 }
 
-impl HintTrait for Hint {}
+/// Displays the hints of the actions available from the focused view,
+/// aligned to the right. Refilled by the application each time the focus
+/// or the actions change.
+pub struct Hints {
+    box_view_data: BoxViewData,
+    hints: Vec<Hint>,
+}
 
+impl Hints {
+    pub fn new() -> Self {
+        let mut s = Self {
+            box_view_data: BoxViewData::default(),
+            hints: vec![],
+        };
+        s.set_axis(Axis::Row);
+        s.set_justify_content(JustifyContent::FlexEnd);
+        s.set_background(ViewBackground::None);
+        s.data_mut().shadow_type = ShadowType::None;
+        s
+    }
+
+    /**
+     * Replaces the displayed hints by the ones of the given view and its parents.
+     */
+    pub fn refill(&mut self, view: Option<Rc<RefCell<View>>>) {
+        self.hints = hint_actions(view).iter().map(Hint::new).collect();
+    }
 
+    pub fn hints(&self) -> &Vec<Hint> {
+        &self.hints
+    }
+}
 
-pub struct Hints;
+impl Default for Hints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 pub trait HintsTrait: BoxTrait {}
 
-impl BoxTrait for Hints {}
+impl HintsTrait for Hints {}
+
+impl BoxTrait for Hints {
+    fn box_view_data(&self) -> &BoxViewData {
+        &self.box_view_data
+    }
+
+    fn box_view_data_mut(&mut self) -> &mut BoxViewData {
+        &mut self.box_view_data
+    }
+}
 
-impl ViewDrawer for Hints {}
+impl ViewDrawer for Hints {
+    fn draw(&self, ctx: &FrameContext, x: f32, y: f32, width: f32, height: f32) {
+        let spacing = style("brls/hints/footer_margin_sides");
+        let center_y = y + height / 2.0;
+
+        // From right to left, so that A ends up in the corner
+        let mut right = x + width;
+        for hint in self.hints.iter().rev() {
+            let left = right - hint.content_width(ctx.vg());
+            if left < x {
+                break;
+            }
+
+            hint.draw_content(ctx.vg(), left, center_y);
+            right = left - spacing;
+        }
+    }
+}
 
 impl ViewLayout for Hints {}
 
@@ -44,12 +224,10 @@ impl ViewStyle for Hints {}
 
 impl ViewBase for Hints {
     fn data(&self) -> &ViewData {
-        todo!()
+        self.box_view_data.view_data()
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        todo!()
+        self.box_view_data.view_data_mut()
     }
 }
-
-impl HintsTrait for Hints {}
\ No newline at end of file
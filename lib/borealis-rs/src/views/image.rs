@@ -1,12 +1,43 @@
-use crate::core::view_base::{ViewBase, ViewData};
+use std::path::PathBuf;
+use crate::core::attribute::XMLAttributes;
+use crate::core::view_base::{View, ViewBase, ViewData};
 use crate::core::view_box::BoxTrait;
 use crate::core::view_drawer::ViewDrawer;
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
 
-pub struct Image;
+/// An image, loaded from a file on disk
+pub struct Image {
+    view_data: ViewData,
+    image_path: Option<PathBuf>,
+}
+
+impl Image {
+    pub fn new() -> Self {
+        Self {
+            view_data: ViewData::default(),
+            image_path: None,
+        }
+    }
+}
+
+pub trait ImageTrait: BoxTrait {
+
+    fn image(&self) -> &Image;
+
+    fn image_mut(&mut self) -> &mut Image;
+
+    /**
+     * Sets the image file to display.
+     */
+    fn set_image_from_file(&mut self, path: PathBuf) {
+        self.image_mut().image_path = Some(path);
+    }
 
-pub trait ImageTrait: BoxTrait {}
+    fn image_path(&self) -> Option<&PathBuf> {
+        self.image().image_path.as_ref()
+    }
+}
 
 impl BoxTrait for Image {}
 
@@ -18,14 +49,29 @@ impl ViewStyle for Image {}
 
 impl ViewBase for Image {
     fn data(&self) -> &ViewData {
-        todo!()
+        &self.view_data
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        todo!()
+        &mut self.view_data
     }
 }
 
 impl ImageTrait for Image {
+    fn image(&self) -> &Image {
+        self
+    }
 
-}
\ No newline at end of file
+    fn image_mut(&mut self) -> &mut Image {
+        self
+    }
+}
+
+/// Attributes only accepted by images.
+pub fn register_image_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_file_path_attribute("image", |view, value| {
+        if let View::Image(image) = view {
+            image.set_image_from_file(value);
+        }
+    });
+}
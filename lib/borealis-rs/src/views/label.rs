@@ -1,8 +1,9 @@
 use nanovg_sys::NVGcolor;
 use crate::core::animation::Animatable;
+use crate::core::attribute::XMLAttributes;
 use crate::core::theme::nvg_rgb;
 use crate::core::time::Time;
-use crate::core::view_base::{ViewBase, ViewData};
+use crate::core::view_base::{View, ViewBase, ViewData};
 use crate::core::view_drawer::{ViewDrawer, ViewTrait};
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
@@ -222,4 +223,60 @@ impl LabelTrait for Label {
     fn label_mut(&mut self) -> &mut Label {
         self
     }
-}
\ No newline at end of file
+}
+
+/// Attributes only accepted by labels.
+pub fn register_label_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_string_attribute("text", |view, value| {
+        if let View::Label(label) = view {
+            label.set_text(value);
+        }
+    });
+    attributes.register_float_attribute("fontSize", |view, value| {
+        if let View::Label(label) = view {
+            label.set_font_size(value);
+        }
+    });
+    attributes.register_float_attribute("fontQuality", |view, value| {
+        if let View::Label(label) = view {
+            label.set_font_quality(value);
+        }
+    });
+    attributes.register_float_attribute("lineHeight", |view, value| {
+        if let View::Label(label) = view {
+            label.set_line_height(value);
+        }
+    });
+    attributes.register_color_attribute("textColor", |view, value| {
+        if let View::Label(label) = view {
+            label.set_text_color(value);
+        }
+    });
+    attributes.register_enum_attribute(
+        "horizontalAlign",
+        &[
+            ("left", HorizontalAlign::Left),
+            ("center", HorizontalAlign::Center),
+            ("right", HorizontalAlign::Right),
+        ],
+        |view, value| {
+            if let View::Label(label) = view {
+                label.set_horizontal_align(value);
+            }
+        },
+    );
+    attributes.register_enum_attribute(
+        "verticalAlign",
+        &[
+            ("baseline", VerticalAlign::Baseline),
+            ("top", VerticalAlign::Top),
+            ("center", VerticalAlign::Center),
+            ("bottom", VerticalAlign::Bottom),
+        ],
+        |view, value| {
+            if let View::Label(label) = view {
+                label.set_vertical_align(value);
+            }
+        },
+    );
+}
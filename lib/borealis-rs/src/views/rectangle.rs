@@ -1,12 +1,39 @@
-use crate::core::view_base::{ViewBase, ViewData};
+use nanovg_sys::NVGcolor;
+use crate::core::theme::nvg_rgb;
+use crate::core::attribute::XMLAttributes;
+use crate::core::view_base::{View, ViewBase, ViewData};
 use crate::core::view_drawer::{ViewDrawer, ViewTrait};
 use crate::core::view_layout::ViewLayout;
 use crate::core::view_style::ViewStyle;
 
-pub struct Rectangle;
+/// A solid color rectangle
+pub struct Rectangle {
+    view_data: ViewData,
+    color: NVGcolor,
+}
+
+impl Rectangle {
+    pub fn new() -> Self {
+        Self {
+            view_data: ViewData::default(),
+            color: nvg_rgb(0, 0, 255),
+        }
+    }
+}
 
 pub trait RectangleTrait: ViewTrait {
 
+    fn rectangle(&self) -> &Rectangle;
+
+    fn rectangle_mut(&mut self) -> &mut Rectangle;
+
+    fn set_color(&mut self, color: NVGcolor) {
+        self.rectangle_mut().color = color;
+    }
+
+    fn color(&self) -> NVGcolor {
+        self.rectangle().color
+    }
 }
 
 impl ViewTrait for Rectangle {}
@@ -19,14 +46,29 @@ impl ViewStyle for Rectangle {}
 
 impl ViewBase for Rectangle {
     fn data(&self) -> &ViewData {
-        todo!()
+        &self.view_data
     }
 
     fn data_mut(&mut self) -> &mut ViewData {
-        todo!()
+        &mut self.view_data
     }
 }
 
 impl RectangleTrait for Rectangle {
+    fn rectangle(&self) -> &Rectangle {
+        self
+    }
 
-}
\ No newline at end of file
+    fn rectangle_mut(&mut self) -> &mut Rectangle {
+        self
+    }
+}
+
+/// Attributes only accepted by rectangles.
+pub fn register_rectangle_attributes(attributes: &mut XMLAttributes) {
+    attributes.register_color_attribute("color", |view, value| {
+        if let View::Rectangle(rectangle) = view {
+            rectangle.set_color(value);
+        }
+    });
+}
@@ -0,0 +1,57 @@
+//! Binds the fields of a struct deriving BindViews to a view tree.
+
+use borealis_rs::core::bind::{BindError, BindViews, BoundView};
+use borealis_rs::core::view_base::View;
+use borealis_rs::core::view_creator::{XMLParser, XMLViewRegistry};
+use borealis_rs::views::image::Image;
+use borealis_rs::views::label::{Label, LabelTrait};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Default, BindViews)]
+struct Screen {
+    #[bind("title")]
+    title: BoundView<Label>,
+
+    #[bind("icon")]
+    icon: BoundView<Image>,
+
+    unbound: BoundView<Label>,
+}
+
+fn parse(xml: &str) -> Rc<RefCell<View>> {
+    XMLParser::new(&XMLViewRegistry::new(), "<test>".into(), xml)
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn annotated_fields_are_bound_to_their_id() {
+    let root = parse(
+        r#"<brls:Box>
+            <brls:Label id="title" text="Hello"/>
+        </brls:Box>"#,
+    );
+    let mut screen = Screen::default();
+    screen.bind_views(&root);
+
+    assert_eq!(screen.title.id(), "title");
+    assert_eq!(screen.title.get().unwrap().full_text(), "Hello");
+    assert_eq!(
+        screen.icon.view().err(),
+        Some(BindError::NotFound { id: "icon".into() })
+    );
+}
+
+#[test]
+fn other_fields_are_left_alone() {
+    let root = parse(r#"<brls:Label id="title"/>"#);
+    let mut screen = Screen::default();
+    screen.bind_views(&root);
+
+    assert_eq!(screen.unbound.id(), "");
+    assert_eq!(
+        screen.unbound.view().err(),
+        Some(BindError::NotBound { id: "".into() })
+    );
+}
@@ -0,0 +1,73 @@
+//! Runs the borealis-lint binary over temporary resources directories.
+
+#[path = "../src/core/temp_dir.rs"]
+mod temp_dir;
+
+use std::process::{Command, Output};
+use temp_dir::TempDir;
+
+fn lint(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_borealis-lint"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn succeeds_without_problems() {
+    let dir = TempDir::with_files(
+        "lint-bin-clean",
+        &[
+            (
+                "xml/main.xml",
+                "<brls:Box><brls:Label text=\"@i18n/demo/title\"/></brls:Box>",
+            ),
+            ("i18n/en-US/demo.json", r#"{ "title": "Demo" }"#),
+        ],
+    );
+
+    let output = lint(&[dir.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert_eq!(stdout(&output), "1 XML file(s) checked, no problem found\n");
+}
+
+#[test]
+fn fails_with_problems() {
+    let dir = TempDir::with_files(
+        "lint-bin-problems",
+        &[(
+            "xml/main.xml",
+            "<brls:Box>\n    <brls:Label width=\"@style/custom\"/>\n</brls:Box>",
+        )],
+    );
+    let main = dir.join("xml/main.xml");
+
+    let output = lint(&[dir.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}:2: <brls:Label>: unresolved reference \"@style/custom\": unknown style\n\
+             1 XML file(s) checked, 1 problem(s) found\n",
+            main.display()
+        )
+    );
+
+    // Styles added by the application are declared on the command line
+    let output = lint(&["--style", "custom=12", dir.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+}
+
+#[test]
+fn usage_errors_exit_with_2() {
+    assert_eq!(lint(&["--unknown"]).status.code(), Some(2));
+    assert_eq!(lint(&["--style", "custom"]).status.code(), Some(2));
+
+    let dir = TempDir::new("lint-bin-missing");
+    let missing = dir.join("missing");
+    assert_eq!(lint(&[missing.to_str().unwrap()]).status.code(), Some(2));
+}
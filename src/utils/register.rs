@@ -1,5 +1,6 @@
 use borealis_rs::core::application::Application;
 
-pub fn init_custom_view(application: &mut Application) {
-    // application.register_xml_view("")
+pub fn init_custom_view(application: &mut Application) -> anyhow::Result<()> {
+    // application.register_xml_view("RecyclingGridItem", Box::new(...))?;
+    Ok(())
 }